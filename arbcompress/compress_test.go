@@ -48,3 +48,19 @@ func TestArbCompress(t *testing.T) {
 	// test empty data:
 	testCompressDecompress(t, []byte{})
 }
+
+func TestDecompressLimit(t *testing.T) {
+	// a highly compressible input that would expand far beyond the limit
+	bomb := bytes.Repeat([]byte{0}, 1<<20)
+	compressed, err := CompressWell(bomb)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if len(compressed) > 1024 {
+		t.Fatal("input not compressible enough", len(compressed))
+	}
+	if _, err := Decompress(compressed, 1024); err == nil {
+		t.Fatal("decompression should abort once the output exceeds the limit")
+	}
+	testDecompress(t, compressed, bomb)
+}
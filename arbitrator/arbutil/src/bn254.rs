@@ -0,0 +1,78 @@
+// Copyright 2023, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+//! The alt_bn128 operations behind the EVM's 0x06, 0x07, and 0x08 precompiles (EIP-196 & EIP-197).
+
+use substrate_bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+
+/// The size of an encoded G1 point.
+pub const G1_LEN: usize = 64;
+
+/// The size of an input to `add`.
+pub const ADD_INPUT_LEN: usize = 2 * G1_LEN;
+
+/// The size of an input to `mul`.
+pub const MUL_INPUT_LEN: usize = G1_LEN + 32;
+
+/// The size of each (G1, G2) pair passed to `pairing`.
+pub const PAIR_LEN: usize = G1_LEN + 128;
+
+/// Adds two G1 points, returning `None` if either is malformed.
+pub fn add(input: &[u8; ADD_INPUT_LEN]) -> Option<[u8; G1_LEN]> {
+    let a = read_g1(&input[..G1_LEN])?;
+    let b = read_g1(&input[G1_LEN..])?;
+    Some(write_g1(a + b))
+}
+
+/// Multiplies a G1 point by a scalar, returning `None` if the point is malformed.
+pub fn mul(input: &[u8; MUL_INPUT_LEN]) -> Option<[u8; G1_LEN]> {
+    let point = read_g1(&input[..G1_LEN])?;
+    let scalar = Fr::from_slice(&input[G1_LEN..]).ok()?;
+    Some(write_g1(point * scalar))
+}
+
+/// Checks whether the product of the pairings is one, returning `None` if the input
+/// isn't a whole number of pairs or any point is malformed.
+pub fn pairing(input: &[u8]) -> Option<bool> {
+    if input.len() % PAIR_LEN != 0 {
+        return None;
+    }
+    let mut pairs = Vec::with_capacity(input.len() / PAIR_LEN);
+    for pair in input.chunks(PAIR_LEN) {
+        let (g1, g2) = pair.split_at(G1_LEN);
+        pairs.push((read_g1(g1)?, read_g2(g2)?));
+    }
+    Some(substrate_bn::pairing_batch(&pairs) == Gt::one())
+}
+
+fn read_fq(data: &[u8]) -> Option<Fq> {
+    Fq::from_slice(data).ok()
+}
+
+fn read_g1(data: &[u8]) -> Option<G1> {
+    let x = read_fq(&data[..32])?;
+    let y = read_fq(&data[32..64])?;
+    if x.is_zero() && y.is_zero() {
+        return Some(G1::zero());
+    }
+    AffineG1::new(x, y).ok().map(Into::into)
+}
+
+/// Reads a G2 point, whose coordinates are each encoded imaginary part first.
+fn read_g2(data: &[u8]) -> Option<G2> {
+    let x = Fq2::new(read_fq(&data[32..64])?, read_fq(&data[..32])?);
+    let y = Fq2::new(read_fq(&data[96..128])?, read_fq(&data[64..96])?);
+    if x.is_zero() && y.is_zero() {
+        return Some(G2::zero());
+    }
+    AffineG2::new(x, y).ok().map(Into::into)
+}
+
+fn write_g1(point: G1) -> [u8; G1_LEN] {
+    let mut output = [0; G1_LEN];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut output[..32]).unwrap();
+        point.y().to_big_endian(&mut output[32..]).unwrap();
+    }
+    output
+}
@@ -43,6 +43,9 @@ pub enum EvmApiMethod {
     AccountBalance,
     AccountCodeHash,
     AddPages,
+    SelfDestruct,
+    AccountNonce,
+    AccountStorageRoot,
 }
 
 pub trait EvmApi: Send + 'static {
@@ -124,13 +127,29 @@ pub trait EvmApi: Send + 'static {
     /// Analogous to `vm.BALANCE`.
     fn account_balance(&mut self, address: Bytes20) -> (Bytes32, u64);
 
+    /// Gets the nonce of the given account.
+    /// Returns the nonce and the access cost in gas.
+    /// Not analogous to any EVM opcode.
+    fn account_nonce(&mut self, address: Bytes20) -> (u64, u64);
+
     /// Gets the hash of the given address's code.
     /// Returns the hash and the access cost in gas.
     /// Analogous to `vm.CODEHASH`.
     fn account_codehash(&mut self, address: Bytes20) -> (Bytes32, u64);
 
+    /// Gets the root of the given account's storage trie.
+    /// Returns the root and the access cost in gas.
+    /// Only meaningful when backed by a state trie, as when verifying proofs against it.
+    /// Not analogous to any EVM opcode.
+    fn account_storage_root(&mut self, address: Bytes20) -> (Bytes32, u64);
+
     /// Determines the cost in gas of allocating additional wasm pages.
     /// Note: has the side effect of updating Geth's memory usage tracker.
     /// Not analogous to any EVM opcode.
     fn add_pages(&mut self, pages: u16) -> u64;
+
+    /// Sends the program's entire balance to the beneficiary.
+    /// Returns the gas cost on success, or an error when the program is read-only.
+    /// Analogous to `vm.SELFDESTRUCT` after EIP-6780, with the code always left in place.
+    fn selfdestruct(&mut self, beneficiary: Bytes20) -> Result<u64>;
 }
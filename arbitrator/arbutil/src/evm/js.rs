@@ -298,13 +298,32 @@ impl<T: JsCallIntoGo> EvmApi for JsEvmApi<T> {
         (value.assert_bytes32(), cost.assert_u64())
     }
 
+    fn account_nonce(&mut self, address: Bytes20) -> (u64, u64) {
+        let [nonce, cost] = call!(self, 2, AccountNonce, address);
+        (nonce.assert_u64(), cost.assert_u64())
+    }
+
     fn account_codehash(&mut self, address: Bytes20) -> (Bytes32, u64) {
         let [value, cost] = call!(self, 2, AccountCodeHash, address);
         (value.assert_bytes32(), cost.assert_u64())
     }
 
+    fn account_storage_root(&mut self, address: Bytes20) -> (Bytes32, u64) {
+        let [value, cost] = call!(self, 2, AccountStorageRoot, address);
+        (value.assert_bytes32(), cost.assert_u64())
+    }
+
     fn add_pages(&mut self, pages: u16) -> u64 {
         let [cost] = call!(self, 1, AddPages, pages);
         cost.assert_u64()
     }
+
+    fn selfdestruct(&mut self, beneficiary: Bytes20) -> Result<u64> {
+        let [out] = call!(self, 1, SelfDestruct, beneficiary);
+        match out {
+            ApiValueKind::U64(cost) => Ok(cost),
+            ApiValueKind::String(err) => bail!(err),
+            _ => unreachable!(),
+        }
+    }
 }
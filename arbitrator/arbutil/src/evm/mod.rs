@@ -1,7 +1,8 @@
 // Copyright 2023, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use crate::{Bytes20, Bytes32};
+use crate::{crypto, Bytes20, Bytes32};
+use eyre::{ensure, Result};
 
 pub mod api;
 pub mod js;
@@ -10,6 +11,27 @@ pub mod user;
 // params.SstoreSentryGasEIP2200
 pub const SSTORE_SENTRY_GAS: u64 = 2300;
 
+// params.SstoreSetGasEIP2200
+pub const SSTORE_SET_GAS: u64 = 20000;
+
+// params.SstoreResetGasEIP2200 - params.ColdSloadCostEIP2929
+pub const SSTORE_RESET_GAS: u64 = 5000 - COLD_SLOAD_GAS;
+
+// params.SstoreClearsScheduleRefundEIP3529
+pub const SSTORE_CLEARS_REFUND: i64 = 4800;
+
+// params.ColdSloadCostEIP2929
+pub const COLD_SLOAD_GAS: u64 = 2100;
+
+// params.ColdAccountAccessCostEIP2929
+pub const COLD_ACCOUNT_GAS: u64 = 2600;
+
+// params.SelfdestructGasEIP150
+pub const SELFDESTRUCT_GAS: u64 = 5000;
+
+// params.WarmStorageReadCostEIP2929
+pub const WARM_ACCESS_GAS: u64 = 100;
+
 // params.LogGas and params.LogDataGas
 pub const LOG_TOPIC_GAS: u64 = 375;
 pub const LOG_DATA_GAS: u64 = 8;
@@ -17,10 +39,24 @@ pub const LOG_DATA_GAS: u64 = 8;
 // params.CopyGas
 pub const COPY_WORD_GAS: u64 = 3;
 
+// params.MemoryGas and params.QuadCoeffDiv
+pub const MEMORY_WORD_GAS: u64 = 3;
+pub const MEMORY_QUAD_COEFF_DIV: u64 = 512;
+
 // params.Keccak256Gas
 pub const KECCAK_256_GAS: u64 = 30;
 pub const KECCAK_WORD_GAS: u64 = 6;
 
+// params.Bn256AddGasIstanbul
+pub const BN254_ADD_GAS: u64 = 150;
+
+// params.Bn256ScalarMulGasIstanbul
+pub const BN254_MUL_GAS: u64 = 6000;
+
+// params.Bn256PairingBaseGasIstanbul and params.Bn256PairingPerPointGasIstanbul
+pub const BN254_PAIRING_BASE_GAS: u64 = 45000;
+pub const BN254_PAIRING_POINT_GAS: u64 = 34000;
+
 // vm.GasQuickStep (see gas.go)
 pub const GAS_QUICK_STEP: u64 = 2;
 
@@ -30,6 +66,12 @@ pub const ADDRESS_GAS: u64 = GAS_QUICK_STEP;
 // vm.GasQuickStep (see eips.go)
 pub const BASEFEE_GAS: u64 = GAS_QUICK_STEP;
 
+// vm.GasQuickStep (see jump_table.go)
+pub const PREVRANDAO_GAS: u64 = GAS_QUICK_STEP;
+
+// vm.GasQuickStep (see EIP-7516)
+pub const BLOBBASEFEE_GAS: u64 = GAS_QUICK_STEP;
+
 // vm.GasQuickStep (see eips.go)
 pub const CHAINID_GAS: u64 = GAS_QUICK_STEP;
 
@@ -64,6 +106,8 @@ pub const ORIGIN_GAS: u64 = GAS_QUICK_STEP;
 #[repr(C)]
 pub struct EvmData {
     pub block_basefee: Bytes32,
+    pub block_prevrandao: Bytes32,
+    pub block_blob_basefee: Bytes32,
     pub chainid: u64,
     pub block_coinbase: Bytes20,
     pub block_gas_limit: u64,
@@ -75,9 +119,153 @@ pub struct EvmData {
     pub tx_gas_price: Bytes32,
     pub tx_origin: Bytes20,
     pub reentrant: u32,
+    pub tx_type: u8,
     pub return_data_len: u32,
 }
 
+impl EvmData {
+    /// Creates an `EvmData` from its component parts, checking that each is in range.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        block_basefee: Bytes32,
+        block_prevrandao: Bytes32,
+        block_blob_basefee: Bytes32,
+        chainid: u64,
+        block_coinbase: Bytes20,
+        block_gas_limit: u64,
+        block_number: u64,
+        block_timestamp: u64,
+        contract_address: Bytes20,
+        msg_sender: Bytes20,
+        msg_value: Bytes32,
+        tx_gas_price: Bytes32,
+        tx_origin: Bytes20,
+        reentrant: u32,
+        tx_type: u8,
+    ) -> Result<Self> {
+        let data = Self::new_unchecked(
+            block_basefee,
+            block_prevrandao,
+            block_blob_basefee,
+            chainid,
+            block_coinbase,
+            block_gas_limit,
+            block_number,
+            block_timestamp,
+            contract_address,
+            msg_sender,
+            msg_value,
+            tx_gas_price,
+            tx_origin,
+            reentrant,
+            tx_type,
+        );
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Like `new`, but leaves validation to the caller.
+    /// FFI paths use this so that invalid data fails the call rather than panicking.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_unchecked(
+        block_basefee: Bytes32,
+        block_prevrandao: Bytes32,
+        block_blob_basefee: Bytes32,
+        chainid: u64,
+        block_coinbase: Bytes20,
+        block_gas_limit: u64,
+        block_number: u64,
+        block_timestamp: u64,
+        contract_address: Bytes20,
+        msg_sender: Bytes20,
+        msg_value: Bytes32,
+        tx_gas_price: Bytes32,
+        tx_origin: Bytes20,
+        reentrant: u32,
+        tx_type: u8,
+    ) -> Self {
+        Self {
+            block_basefee,
+            block_prevrandao,
+            block_blob_basefee,
+            chainid,
+            block_coinbase,
+            block_gas_limit,
+            block_number,
+            block_timestamp,
+            contract_address,
+            msg_sender,
+            msg_value,
+            tx_gas_price,
+            tx_origin,
+            reentrant,
+            tx_type,
+            return_data_len: 0,
+        }
+    }
+
+    /// Checks the invariants the hostios rely on, since Go may hand over the struct whole.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(self.chainid != 0, "chainid must be nonzero");
+        ensure!(self.block_gas_limit != 0, "block gas limit must be nonzero");
+        ensure!(
+            self.tx_type < 0x80,
+            "tx type {:#x} is outside the EIP-2718 range",
+            self.tx_type
+        );
+        ensure!(
+            self.reentrant <= 1,
+            "reentrant must be 0 or 1, not {}",
+            self.reentrant
+        );
+        Ok(())
+    }
+
+    /// Whether the current call is reentrant.
+    pub fn is_reentrant(&self) -> bool {
+        self.reentrant != 0
+    }
+}
+
+/// Returns the gas cost and refund delta of an `SSTORE`, following EIP-2200 as amended by EIP-2929 and EIP-3529.
+/// `original` is the slot's value at the start of the transaction.
+pub fn sstore_cost(original: Bytes32, current: Bytes32, new: Bytes32, cold: bool) -> (u64, i64) {
+    let zero = Bytes32::default();
+    let mut cost = if cold { COLD_SLOAD_GAS } else { 0 };
+    let mut refund = 0;
+
+    if current == new {
+        return (cost + WARM_ACCESS_GAS, refund); // noop
+    }
+    if original == current {
+        if original == zero {
+            return (cost + SSTORE_SET_GAS, refund); // create slot
+        }
+        if new == zero {
+            refund += SSTORE_CLEARS_REFUND; // delete slot
+        }
+        return (cost + SSTORE_RESET_GAS, refund); // write existing slot
+    }
+
+    // the slot is dirty
+    cost += WARM_ACCESS_GAS;
+    if original != zero {
+        if current == zero {
+            refund -= SSTORE_CLEARS_REFUND; // recreate slot
+        } else if new == zero {
+            refund += SSTORE_CLEARS_REFUND; // delete slot
+        }
+    }
+    if original == new {
+        let reset = match original == zero {
+            true => SSTORE_SET_GAS,    // reset to original nonexistent slot
+            false => SSTORE_RESET_GAS, // reset to original existing slot
+        };
+        refund += (reset - WARM_ACCESS_GAS) as i64;
+    }
+    (cost, refund)
+}
+
 /// Returns the minimum number of EVM words needed to store `bytes` bytes.
 pub fn evm_words(bytes: u64) -> u64 {
     match bytes % 32 {
@@ -85,3 +273,33 @@ pub fn evm_words(bytes: u64) -> u64 {
         _ => bytes / 32 + 1,
     }
 }
+
+/// The total gas the EVM charges for `words` words of active memory: `3 * words + words² / 512`.
+pub fn memory_gas(words: u64) -> u64 {
+    let linear = words.saturating_mul(MEMORY_WORD_GAS);
+    let quadratic = words.saturating_mul(words) / MEMORY_QUAD_COEFF_DIV;
+    linear.saturating_add(quadratic)
+}
+
+/// Grows `active` words of memory to cover `len` bytes at `offset`, as the EVM would.
+/// Returns the new number of active words and the gas owed for the expansion.
+pub fn memory_expansion(active: u64, offset: u32, len: u32) -> (u64, u64) {
+    if len == 0 {
+        return (active, 0);
+    }
+    let words = evm_words(offset as u64 + len as u64);
+    if words <= active {
+        return (active, 0);
+    }
+    (words, memory_gas(words) - memory_gas(active))
+}
+
+/// Computes the address `CREATE2` would deploy to, as keccak256(0xff ++ sender ++ salt ++ code_hash)[12..].
+pub fn create2_address(sender: Bytes20, salt: Bytes32, code_hash: Bytes32) -> Bytes20 {
+    let mut preimage = Vec::with_capacity(1 + 20 + 32 + 32);
+    preimage.push(0xff);
+    preimage.extend(sender.as_slice());
+    preimage.extend(salt.as_slice());
+    preimage.extend(code_hash.as_slice());
+    crypto::keccak(preimage)[12..].try_into().unwrap()
+}
@@ -11,6 +11,8 @@ pub enum UserOutcome {
     Failure(ErrReport),
     OutOfInk,
     OutOfStack,
+    Interrupted,
+    OutOfMemory,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -21,6 +23,8 @@ pub enum UserOutcomeKind {
     Failure,
     OutOfInk,
     OutOfStack,
+    Interrupted,
+    OutOfMemory,
 }
 
 impl UserOutcome {
@@ -49,6 +53,8 @@ impl From<&UserOutcome> for UserOutcomeKind {
             Failure(_) => Self::Failure,
             OutOfInk => Self::OutOfInk,
             OutOfStack => Self::OutOfStack,
+            Interrupted => Self::Interrupted,
+            OutOfMemory => Self::OutOfMemory,
         }
     }
 }
@@ -73,6 +79,8 @@ impl Display for UserOutcome {
             Failure(err) => write!(f, "failure {:?}", err),
             OutOfInk => write!(f, "out of ink"),
             OutOfStack => write!(f, "out of stack"),
+            Interrupted => write!(f, "interrupted"),
+            OutOfMemory => write!(f, "out of memory"),
             Revert(data) => {
                 let text = String::from_utf8(data.clone()).unwrap_or_else(|_| hex::encode(data));
                 write!(f, "revert {text}")
@@ -91,6 +99,8 @@ impl Display for UserOutcomeKind {
             Failure => write!(f, "failure ({as_u8})"),
             OutOfInk => write!(f, "out of ink ({as_u8})"),
             OutOfStack => write!(f, "out of stack ({as_u8})"),
+            Interrupted => write!(f, "interrupted ({as_u8})"),
+            OutOfMemory => write!(f, "out of memory ({as_u8})"),
         }
     }
 }
@@ -1,6 +1,8 @@
 // Copyright 2022-2023, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
+pub mod blake2f;
+pub mod bn254;
 /// cbindgen:ignore
 pub mod color;
 pub mod crypto;
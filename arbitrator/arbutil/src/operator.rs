@@ -12,6 +12,41 @@ pub struct OperatorCode(usize);
 impl OperatorCode {
     // TODO: use std::mem::variant_count when it's stabilized
     pub const OPERATOR_COUNT: usize = 529;
+
+    /// The number of slots needed to index every operator via `dense_index`
+    pub const DENSE_SLOTS: usize = 0x400;
+
+    /// Maps the code to a small index unique among operators, suitable for table lookups.
+    /// Prefixed opcodes (0xfc, 0xfd, and 0xfe) each get their own page of 256 slots.
+    pub fn dense_index(&self) -> usize {
+        match self.0 >> 8 {
+            0 => self.0,
+            prefix => ((prefix - 0xfb) << 8) | (self.0 & 0xff),
+        }
+    }
+
+    /// Recovers the code an index from `dense_index` was derived from.
+    pub fn from_dense_index(index: usize) -> Self {
+        match index >> 8 {
+            0 => Self(index),
+            page => Self(((page + 0xfb) << 8) | (index & 0xff)),
+        }
+    }
+
+    /// Whether the operator involves floats, including conversions to and from them.
+    pub fn is_float(&self) -> bool {
+        match self.0 {
+            0x2a | 0x2b | 0x38 | 0x39 | 0x43 | 0x44 => true, // loads, stores, and constants
+            0x5b..=0x66 | 0x8b..=0xa6 => true,               // comparisons and arithmetic
+            0xa8..=0xab | 0xae..=0xbf => true,               // conversions
+            0xfc00..=0xfc07 => true,                         // saturating truncations
+            0xfd13 | 0xfd14 | 0xfd1f..=0xfd22 | 0xfd41..=0xfd4c => true, // simd
+            0xfd5e | 0xfd5f | 0xfd67..=0xfd6a | 0xfd74 | 0xfd75 | 0xfd7a | 0xfd94 => true,
+            0xfda5 | 0xfda6 | 0xfdaf | 0xfdb0 | 0xfdb4 | 0xfdc5 | 0xfdc6 => true,
+            0xfdcf | 0xfdd0 | 0xfdd4 | 0xfde0..=0xfdff => true,
+            _ => false,
+        }
+    }
 }
 
 impl Display for OperatorCode {
@@ -552,6 +587,12 @@ impl Display for OperatorCode {
     }
 }
 
+impl From<OperatorCode> for usize {
+    fn from(code: OperatorCode) -> Self {
+        code.0
+    }
+}
+
 impl<'a> From<Operator<'a>> for OperatorCode {
     fn from(op: Operator) -> Self {
         OperatorCode::from(&op)
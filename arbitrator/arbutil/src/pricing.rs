@@ -9,3 +9,127 @@ pub const PTR_INK: u64 = 22137 - HOSTIO_INK;
 
 /// For hostios that involve an API cost.
 pub const EVM_API_INK: u64 = 59673;
+
+macro_rules! hostio_kinds {
+    ($($kind:ident => $name:literal),* $(,)?) => {
+        /// Identifies a hostio for pricing purposes.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u8)]
+        pub enum HostioKind {
+            $($kind),*
+        }
+
+        impl HostioKind {
+            pub const ALL: &'static [HostioKind] = &[$(HostioKind::$kind),*];
+            pub const COUNT: usize = Self::ALL.len();
+
+            /// The name programs import the hostio by.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(HostioKind::$kind => $name),*
+                }
+            }
+        }
+    };
+}
+
+hostio_kinds!(
+    ReadArgs => "read_args",
+    ReadArgsSlice => "read_args_slice",
+    ArgsLen => "args_len",
+    WriteResult => "write_result",
+    Abort => "abort",
+    StorageLoadBytes32 => "storage_load_bytes32",
+    StorageStoreBytes32 => "storage_store_bytes32",
+    CallContract => "call_contract",
+    DelegateCallContract => "delegate_call_contract",
+    StaticCallContract => "static_call_contract",
+    Create1 => "create1",
+    Create2 => "create2",
+    ReadReturnData => "read_return_data",
+    ReadReturnDataSlice => "read_return_data_slice",
+    ReturnDataSize => "return_data_size",
+    EmitLog => "emit_log",
+    EmitLogSeparate => "emit_log_separate",
+    AccountBalance => "account_balance",
+    AccountCodehash => "account_codehash",
+    AccountStorageRoot => "account_storage_root",
+    EvmGasLeft => "evm_gas_left",
+    EvmInkLeft => "evm_ink_left",
+    BlockBasefee => "block_basefee",
+    BlockPrevrandao => "block_prevrandao",
+    BlockBlobBasefee => "block_blob_basefee",
+    Chainid => "chainid",
+    BlockCoinbase => "block_coinbase",
+    BlockGasLimit => "block_gas_limit",
+    BlockNumber => "block_number",
+    BlockTimestamp => "block_timestamp",
+    ContractAddress => "contract_address",
+    MsgReentrant => "msg_reentrant",
+    MsgSender => "msg_sender",
+    MsgValue => "msg_value",
+    NativeKeccak256 => "native_keccak256",
+    TxGasPrice => "tx_gas_price",
+    TxInkPrice => "tx_ink_price",
+    TxOrigin => "tx_origin",
+    TxType => "tx_type",
+    SelfDestruct => "selfdestruct",
+    AccountNonce => "account_nonce",
+    Create2Address => "create2_address",
+    Bn254Add => "lib_bn254_add",
+    Bn254Mul => "lib_bn254_mul",
+    Bn254Pairing => "lib_bn254_pairing",
+    Blake2f => "lib_blake2f",
+    ReadPreimage => "read_preimage",
+);
+
+impl HostioKind {
+    /// The ink charged upon entering the hostio, before any input-dependent costs.
+    pub const fn ink(self) -> u64 {
+        use HostioKind::*;
+        let extra = match self {
+            StorageLoadBytes32 | StorageStoreBytes32 => 2 * PTR_INK + EVM_API_INK,
+            CallContract | DelegateCallContract | StaticCallContract => 3 * PTR_INK + EVM_API_INK,
+            Create1 => 3 * PTR_INK + EVM_API_INK,
+            Create2 => 4 * PTR_INK + EVM_API_INK,
+            Create2Address => 3 * PTR_INK,
+            AccountBalance | AccountCodehash | AccountStorageRoot => 2 * PTR_INK + EVM_API_INK,
+            SelfDestruct | AccountNonce => PTR_INK + EVM_API_INK,
+            ReadReturnData | ReadReturnDataSlice => EVM_API_INK,
+            EmitLog => EVM_API_INK,
+            EmitLogSeparate => PTR_INK + EVM_API_INK,
+            BlockBasefee | BlockPrevrandao | BlockBlobBasefee => PTR_INK,
+            BlockCoinbase | ContractAddress | MsgSender | MsgValue => PTR_INK,
+            TxGasPrice | TxOrigin => PTR_INK,
+            Bn254Add | Bn254Mul | Bn254Pairing => 2 * PTR_INK,
+            Blake2f => 2 * PTR_INK,
+            ReadPreimage => 2 * PTR_INK,
+            _ => 0,
+        };
+        HOSTIO_INK + extra
+    }
+}
+
+/// The ink charged upon entering each hostio, indexed by `HostioKind`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostioCosts([u64; HostioKind::COUNT]);
+
+impl HostioCosts {
+    pub fn get(&self, kind: HostioKind) -> u64 {
+        self.0[kind as usize]
+    }
+
+    pub fn set(&mut self, kind: HostioKind, ink: u64) {
+        self.0[kind as usize] = ink;
+    }
+}
+
+impl Default for HostioCosts {
+    fn default() -> Self {
+        let mut costs = [0; HostioKind::COUNT];
+        for &kind in HostioKind::ALL {
+            costs[kind as usize] = kind.ink();
+        }
+        Self(costs)
+    }
+}
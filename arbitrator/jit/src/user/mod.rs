@@ -89,11 +89,29 @@ pub fn call_user_wasm(env: WasmEnvMut, sp: u32) -> MaybeEscape {
     // buy ink
     let pricing = config.pricing;
     let gas = sp.read_go_ptr();
-    let ink = pricing.gas_to_ink(sp.read_u64_raw(gas));
+    let ink = pricing.try_gas_to_ink(sp.read_u64_raw(gas));
 
     // skip the root since we don't use these
     sp.skip_u64();
 
+    // take all gas when the conversion overflows
+    let ink = match ink {
+        Ok(ink) => ink,
+        Err(error) => {
+            let (kind, outs) = Failure(error.wrap_err("call failed")).into_data();
+            sp.write_u8(kind.into()).skip_space();
+            sp.write_ptr(heapify(outs));
+            sp.write_u64_raw(gas, 0);
+            return Ok(());
+        }
+    };
+    if let Err(error) = evm_data.validate() {
+        let (kind, outs) = Failure(error.wrap_err("call failed")).into_data();
+        sp.write_u8(kind.into()).skip_space();
+        sp.write_ptr(heapify(outs));
+        return Ok(());
+    }
+
     let result = exec_wasm(
         sp, env, module, calldata, compile, config, evm_api, evm_data, ink,
     );
@@ -188,32 +206,36 @@ pub fn rust_config_impl(env: WasmEnvMut, sp: u32) {
 ///
 /// The Go compiler expects the call to take the form
 ///     λ(
-///         blockBasefee *[32]byte, chainid u64, blockCoinbase *[20]byte, blockGasLimit,
-///         blockNumber, blockTimestamp u64, contractAddress, msgSender *[20]byte,
-///         msgValue, txGasPrice *[32]byte, txOrigin *[20]byte, reentrant u32,
+///         blockBasefee, blockPrevrandao, blockBlobBasefee *[32]byte, chainid u64,
+///         blockCoinbase *[20]byte, blockGasLimit, blockNumber, blockTimestamp u64,
+///         contractAddress, msgSender *[20]byte, msgValue, txGasPrice *[32]byte,
+///         txOrigin *[20]byte, reentrant u32, txType u8,
 ///     ) -> *EvmData
 ///
 /// These values are placed on the stack as follows
-///     || baseFee || chainid || coinbase || gas limit || block number || timestamp || address ||
-///     || sender || value || gas price || origin || reentrant | 4 pad || data ptr ||
+///     || baseFee || prevrandao || blob baseFee || chainid || coinbase || gas limit ||
+///     || block number || timestamp || address || sender || value || gas price || origin ||
+///     || reentrant | tx type | 3 pad || data ptr ||
 ///
 pub fn evm_data_impl(env: WasmEnvMut, sp: u32) {
     let mut sp = GoStack::simple(sp, &env);
-    let evm_data = EvmData {
-        block_basefee: sp.read_bytes32().into(),
-        chainid: sp.read_u64(),
-        block_coinbase: sp.read_bytes20().into(),
-        block_gas_limit: sp.read_u64(),
-        block_number: sp.read_u64(),
-        block_timestamp: sp.read_u64(),
-        contract_address: sp.read_bytes20().into(),
-        msg_sender: sp.read_bytes20().into(),
-        msg_value: sp.read_bytes32().into(),
-        tx_gas_price: sp.read_bytes32().into(),
-        tx_origin: sp.read_bytes20().into(),
-        reentrant: sp.read_u32(),
-        return_data_len: 0,
-    };
+    let evm_data = EvmData::new_unchecked(
+        sp.read_bytes32().into(),
+        sp.read_bytes32().into(),
+        sp.read_bytes32().into(),
+        sp.read_u64(),
+        sp.read_bytes20().into(),
+        sp.read_u64(),
+        sp.read_u64(),
+        sp.read_u64(),
+        sp.read_bytes20().into(),
+        sp.read_bytes20().into(),
+        sp.read_bytes32().into(),
+        sp.read_bytes32().into(),
+        sp.read_bytes20().into(),
+        sp.read_u32(),
+        sp.read_u8(),
+    );
     sp.skip_space();
     sp.write_ptr(heapify(evm_data));
 }
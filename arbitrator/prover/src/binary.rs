@@ -3,13 +3,20 @@
 
 use crate::{
     programs::{
-        config::CompileConfig, counter::Counter, depth::DepthChecker, dynamic::DynamicMeter,
-        heap::HeapBound, meter::Meter, start::StartMover, FuncMiddleware, Middleware, ModuleMod,
-        StylusData, STYLUS_ENTRY_POINT,
+        config::{CompileConfig, CompileParseLimits},
+        counter::Counter,
+        depth::DepthChecker,
+        dynamic::DynamicMeter,
+        float::NoFloat,
+        grow::GrowMeter,
+        heap::HeapBound,
+        meter::Meter,
+        start::{self, StartMover},
+        FuncMiddleware, Middleware, ModuleMod, StylusData,
     },
     value::{ArbValueType, FunctionType, IntegerValType, Value},
 };
-use arbutil::{Color, DebugColor};
+use arbutil::{operator::OperatorCode, Color, DebugColor};
 use eyre::{bail, ensure, eyre, Result, WrapErr};
 use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use nom::{
@@ -20,7 +27,7 @@ use nom::{
 };
 use serde::{Deserialize, Serialize};
 use std::{convert::TryInto, fmt::Debug, hash::Hash, mem, path::Path, str::FromStr};
-use wasmer_types::{entity::EntityRef, FunctionIndex, LocalFunctionIndex};
+use wasmer_types::{LocalFunctionIndex, WASM_PAGE_SIZE};
 use wasmparser::{
     Data, Element, Export, ExternalKind, Global, Import, ImportSectionEntryType, MemoryType, Name,
     NameSectionReader, Naming, Operator, Parser, Payload, TableType, Type, TypeDef, Validator,
@@ -292,8 +299,51 @@ pub struct WasmBinary<'a> {
     pub names: NameCustomSection,
 }
 
-pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
-    let features = WasmFeatures {
+/// Scans for unsupported wasm extensions that a newer toolchain may emit by default,
+/// producing an actionable error rather than an opaque validation failure.
+fn check_extensions(input: &[u8]) -> Result<()> {
+    use Operator::*;
+
+    for payload in Parser::new(0).parse_all(input) {
+        // malformed wasms are reported by the validator
+        let Ok(Payload::CodeSectionEntry(code)) = payload else {
+            continue;
+        };
+        let Ok(mut ops) = code.get_operators_reader() else {
+            continue;
+        };
+        while !ops.eof() {
+            let Ok(op) = ops.read() else {
+                break;
+            };
+            let feature = match op {
+                RefNull { .. } | RefIsNull | RefFunc { .. } | TypedSelect { .. } => {
+                    "reference-types"
+                }
+                TableGet { .. } | TableSet { .. } | TableGrow { .. } | TableSize { .. } => {
+                    "reference-types"
+                }
+                TableFill { .. } => "reference-types",
+                MemoryInit { .. } | DataDrop { .. } | TableInit { .. } | ElemDrop { .. } => {
+                    "bulk-memory"
+                }
+                TableCopy { .. } => "bulk-memory",
+                _ => continue,
+            };
+            bail!(
+                "{} not supported (found {}); compile with -C target-feature=-{}",
+                feature.red(),
+                OperatorCode::from(&op).red(),
+                feature,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The wasm extensions the validator permits, which every instrumentation pass must handle.
+pub fn wasm_features() -> WasmFeatures {
+    WasmFeatures {
         mutable_global: true,
         saturating_float_to_int: true,
         sign_extension: true,
@@ -310,9 +360,21 @@ pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
         exceptions: false,
         memory64: false,
         extended_const: false,
-    };
+    }
+}
+
+pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
+    parse_with_limits(input, path, &CompileParseLimits::default())
+}
+
+/// Parses a wasm, bailing early if it declares more items than the limits allow.
+pub fn parse_with_limits<'a>(
+    input: &'a [u8],
+    path: &'_ Path,
+    limits: &CompileParseLimits,
+) -> Result<WasmBinary<'a>> {
     let mut validator = Validator::new();
-    validator.wasm_features(features);
+    validator.wasm_features(wasm_features());
     validator
         .validate_all(input)
         .wrap_err_with(|| eyre!("failed to validate {}", path.to_string_lossy().red()))?;
@@ -342,8 +404,23 @@ pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
             }};
         }
 
+        macro_rules! limit {
+            ($limit:expr, $source:expr, $name:expr) => {{
+                let count = $source.get_count();
+                if count > $limit {
+                    bail!(
+                        "too many wasm {}: {} > {}",
+                        $name,
+                        count.red(),
+                        $limit.red()
+                    );
+                }
+            }};
+        }
+
         match &mut section {
             TypeSection(type_section) => {
+                limit!(limits.max_types, type_section, "types");
                 for _ in 0..type_section.get_count() {
                     let ty = match type_section.read()? {
                         TypeDef::Func(ty) => ty,
@@ -375,6 +452,7 @@ pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
                 binary.codes.push(code);
             }
             GlobalSection(globals) => {
+                limit!(limits.max_globals, globals, "globals");
                 for global in flatten!(Global, globals) {
                     let mut init = global.init_expr.get_operators_reader();
 
@@ -382,10 +460,20 @@ pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
                         (op, Operator::End, true) => op_as_const(op)?,
                         _ => bail!("Non-constant global initializer"),
                     };
+                    let ty = ArbValueType::try_from(global.ty.content_type)?;
+                    if value.ty() != ty {
+                        bail!(
+                            "global of type {} initialized with {} {}",
+                            ty.red(),
+                            value.ty().red(),
+                            value.red(),
+                        );
+                    }
                     binary.globals.push(value);
                 }
             }
             ImportSection(imports) => {
+                limit!(limits.max_imports, imports, "imports");
                 for import in flatten!(Import, imports) {
                     let ImportSectionEntryType::Function(offset) = import.ty else {
                         bail!("unsupported import kind {:?}", import)
@@ -417,12 +505,21 @@ pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
                     }
                 }
             }
-            FunctionSection(functions) => process!(binary.functions, functions),
+            FunctionSection(functions) => {
+                limit!(limits.max_functions, functions, "functions");
+                process!(binary.functions, functions)
+            }
             TableSection(tables) => process!(binary.tables, tables),
             MemorySection(memories) => process!(binary.memories, memories),
             StartSection { func, .. } => binary.start = Some(*func),
-            ElementSection(elements) => process!(binary.elements, elements),
-            DataSection(datas) => process!(binary.datas, datas),
+            ElementSection(elements) => {
+                limit!(limits.max_element_segments, elements, "element segments");
+                process!(binary.elements, elements)
+            }
+            DataSection(datas) => {
+                limit!(limits.max_data_segments, datas, "data segments");
+                process!(binary.datas, datas)
+            }
             CodeSectionStart { .. } => {}
             CustomSection {
                 name,
@@ -488,8 +585,10 @@ pub fn parse<'a>(input: &'a [u8], path: &'_ Path) -> Result<WasmBinary<'a>> {
     }
 
     // reject the module if it imports or exports reserved symbols
+    // note: start names are exempt since the start mover avoids them
     let reserved = |x: &&str| x.starts_with("stylus");
-    if let Some(name) = exports.into_iter().find(reserved) {
+    let reserved_export = |x: &&str| reserved(x) && start::start_suffix(x).is_none();
+    if let Some(name) = exports.into_iter().find(reserved_export) {
         bail!("binary exports reserved symbol {}", name.red())
     }
     if let Some(name) = binary.imports.iter().filter_map(|x| x.name).find(reserved) {
@@ -528,14 +627,21 @@ impl<'a> Debug for WasmBinary<'a> {
 impl<'a> WasmBinary<'a> {
     /// Instruments a user wasm, producing a version bounded via configurable instrumentation.
     pub fn instrument(&mut self, compile: &CompileConfig) -> Result<StylusData> {
-        let meter = Meter::new(compile.pricing.costs);
-        let dygas = DynamicMeter::new(&compile.pricing);
+        let pricing = compile.metered_pricing();
+        let meter = Meter::new(pricing.pricer());
+        let dygas = DynamicMeter::new(&pricing);
+        let grow = GrowMeter::new(&pricing);
         let depth = DepthChecker::new(compile.bounds);
         let bound = HeapBound::new(compile.bounds);
         let start = StartMover::default();
 
+        let float = compile.forbid_floats.then(NoFloat::default);
+        if let Some(float) = &float {
+            float.update_module(self)?;
+        }
         meter.update_module(self)?;
         dygas.update_module(self)?;
+        grow.update_module(self)?;
         depth.update_module(self)?;
         bound.update_module(self)?;
         start.update_module(self)?;
@@ -569,8 +675,12 @@ impl<'a> WasmBinary<'a> {
 
             // add the instrumentation in the order of application
             // note: this must be consistent with native execution
+            if let Some(float) = &float {
+                apply!(*float);
+            }
             apply!(meter);
             apply!(dygas);
+            apply!(grow);
             apply!(depth);
             apply!(bound);
             apply!(start);
@@ -584,6 +694,7 @@ impl<'a> WasmBinary<'a> {
 
         // 4GB maximum implies `footprint` fits in a u16
         let footprint = self.memory_info()?.min.0 as u16;
+        let heap_bound = compile.bounds.heap_bound.0.try_into().unwrap_or(u16::MAX);
 
         let [ink_left, ink_status] = meter.globals();
         let depth_left = depth.globals();
@@ -592,6 +703,8 @@ impl<'a> WasmBinary<'a> {
             ink_status,
             depth_left,
             footprint,
+            heap_bound,
+            start_export: start.export(),
         })
     }
 
@@ -601,7 +714,23 @@ impl<'a> WasmBinary<'a> {
         page_limit: u16,
         compile: &CompileConfig,
     ) -> Result<(WasmBinary<'a>, StylusData, u16)> {
-        let mut bin = parse(wasm, Path::new("user"))?;
+        let path = Path::new("user");
+        check_extensions(wasm)
+            .wrap_err_with(|| eyre!("failed to validate {}", path.to_string_lossy().red()))?;
+
+        let mut bin = parse_with_limits(wasm, path, &compile.limits)?;
+        bin.require_entrypoint()?;
+
+        // a program can't declare more data than its memory could ever hold
+        let data_size: u64 = bin.datas.iter().map(|data| data.data.len() as u64).sum();
+        let heap_size = compile.bounds.heap_bound.0 as u64 * WASM_PAGE_SIZE as u64;
+        if data_size > heap_size {
+            bail!(
+                "data segments exceed the heap bound: {} > {} bytes",
+                data_size.red(),
+                heap_size.red()
+            );
+        }
         let stylus_data = bin.instrument(compile)?;
 
         let Some(memory) = bin.memories.first() else {
@@ -655,26 +784,6 @@ impl<'a> WasmBinary<'a> {
             bail!("wasm start functions not allowed");
         }
 
-        // check the entrypoint
-        let Some(&(entrypoint, kind)) = bin.exports.get(STYLUS_ENTRY_POINT) else {
-            bail!("missing export with name {}", STYLUS_ENTRY_POINT.red());
-        };
-        if kind != ExportKind::Func {
-            bail!(
-                "export {} must be a function but is a {}",
-                STYLUS_ENTRY_POINT.red(),
-                kind.debug_red(),
-            );
-        }
-        let entrypoint_ty = bin.get_function(FunctionIndex::new(entrypoint.try_into()?))?;
-        if entrypoint_ty != FunctionType::new(vec![ArbValueType::I32], vec![ArbValueType::I32]) {
-            bail!(
-                "wrong type for {}: {}",
-                STYLUS_ENTRY_POINT.red(),
-                entrypoint_ty.red(),
-            );
-        }
-
         Ok((bin, stylus_data, pages as u16))
     }
 }
@@ -0,0 +1,442 @@
+// Copyright 2023, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use crate::{
+    binary::{self, ExportKind, WasmBinary},
+    programs::config::CompileConfig,
+    value::{ArbValueType, Value},
+};
+use arbutil::operator::OperatorCode;
+use eyre::{bail, ensure, Result};
+use std::path::Path;
+use wasmparser::{
+    DataKind, ElementItem, ElementKind, InitExpr, MemoryImmediate, Operator, Type, TypeOrFuncType,
+};
+
+/// Instruments a user wasm and re-encodes it, so that the middlewares' output may be disassembled.
+/// This is a debugging aid and is only available in debug mode.
+pub fn instrumented_wasm(wasm: &[u8], compile: &CompileConfig) -> Result<Vec<u8>> {
+    ensure!(
+        compile.debug.debug_funcs,
+        "dumping instrumented wasms requires debug mode"
+    );
+    let mut bin = binary::parse_with_limits(wasm, Path::new("user"), &compile.limits)?;
+    bin.instrument(compile)?;
+    bin.serialize()
+}
+
+impl<'a> WasmBinary<'a> {
+    /// Encodes the binary back into the wasm format.
+    /// Global mutability isn't tracked, so every global is encoded as mutable.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let mut wasm = b"\0asm".to_vec();
+        wasm.extend(1_u32.to_le_bytes());
+
+        let mut data = vec![];
+        for ty in &self.types {
+            data.push(0x60);
+            for types in [&ty.inputs, &ty.outputs] {
+                write_u32(&mut data, types.len() as u32);
+                for ty in types {
+                    data.push(arb_type_byte(*ty));
+                }
+            }
+        }
+        write_section(&mut wasm, 1, self.types.len(), data);
+
+        let mut data = vec![];
+        for import in &self.imports {
+            let Some(name) = import.name else {
+                bail!("import from {} has no name", import.module)
+            };
+            write_name(&mut data, import.module);
+            write_name(&mut data, name);
+            data.push(0x00);
+            write_u32(&mut data, import.offset);
+        }
+        write_section(&mut wasm, 2, self.imports.len(), data);
+
+        let mut data = vec![];
+        for ty in &self.functions {
+            write_u32(&mut data, *ty);
+        }
+        write_section(&mut wasm, 3, self.functions.len(), data);
+
+        let mut data = vec![];
+        for table in &self.tables {
+            data.push(type_byte(table.element_type)?);
+            let max = table.maximum.map(Into::into);
+            write_limits(&mut data, table.initial.into(), max, false);
+        }
+        write_section(&mut wasm, 4, self.tables.len(), data);
+
+        let mut data = vec![];
+        for memory in &self.memories {
+            ensure!(!memory.memory64, "64-bit memories are not supported");
+            write_limits(&mut data, memory.initial, memory.maximum, memory.shared);
+        }
+        write_section(&mut wasm, 5, self.memories.len(), data);
+
+        let mut data = vec![];
+        for global in &self.globals {
+            data.push(arb_type_byte(global.ty()));
+            data.push(0x01);
+            write_const(&mut data, *global)?;
+        }
+        write_section(&mut wasm, 6, self.globals.len(), data);
+
+        let mut exports: Vec<_> = self.exports.iter().collect();
+        exports.sort_by_key(|(name, _)| name.as_str());
+        let mut data = vec![];
+        for (name, (index, kind)) in &exports {
+            write_name(&mut data, name);
+            data.push(match kind {
+                ExportKind::Func => 0x00,
+                ExportKind::Table => 0x01,
+                ExportKind::Memory => 0x02,
+                ExportKind::Global => 0x03,
+                ExportKind::Tag => 0x04,
+            });
+            write_u32(&mut data, *index);
+        }
+        write_section(&mut wasm, 7, exports.len(), data);
+
+        if let Some(start) = self.start {
+            let mut data = vec![];
+            write_u32(&mut data, start);
+            write_raw_section(&mut wasm, 8, data);
+        }
+
+        let mut data = vec![];
+        for element in &self.elements {
+            let mut items = element.items.get_items_reader()?;
+            match &element.kind {
+                ElementKind::Active {
+                    table_index: 0,
+                    init_expr,
+                } => {
+                    data.push(0x00);
+                    write_init_expr(&mut data, init_expr)?;
+                }
+                ElementKind::Active {
+                    table_index,
+                    init_expr,
+                } => {
+                    data.push(0x02);
+                    write_u32(&mut data, *table_index);
+                    write_init_expr(&mut data, init_expr)?;
+                    data.push(0x00);
+                }
+                ElementKind::Passive => data.extend([0x01, 0x00]),
+                ElementKind::Declared => data.extend([0x03, 0x00]),
+            }
+            let count = items.get_count();
+            write_u32(&mut data, count);
+            for _ in 0..count {
+                let ElementItem::Func(index) = items.read()? else {
+                    bail!("element expressions are not supported")
+                };
+                write_u32(&mut data, index);
+            }
+        }
+        write_section(&mut wasm, 9, self.elements.len(), data);
+
+        // bulk memory operators reference data segments, which requires declaring their count
+        if !self.datas.is_empty() {
+            let mut data = vec![];
+            write_u32(&mut data, self.datas.len() as u32);
+            write_raw_section(&mut wasm, 12, data);
+        }
+
+        let mut data = vec![];
+        for code in &self.codes {
+            let mut body = vec![];
+            let mut groups: Vec<(u32, ArbValueType)> = vec![];
+            for local in &code.locals {
+                match groups.last_mut() {
+                    Some((count, ty)) if *ty == local.value => *count += 1,
+                    _ => groups.push((1, local.value)),
+                }
+            }
+            write_u32(&mut body, groups.len() as u32);
+            for (count, ty) in groups {
+                write_u32(&mut body, count);
+                body.push(arb_type_byte(ty));
+            }
+            for op in &code.expr {
+                write_op(&mut body, op)?;
+            }
+            write_u32(&mut data, body.len() as u32);
+            data.extend(body);
+        }
+        write_section(&mut wasm, 10, self.codes.len(), data);
+
+        let mut data = vec![];
+        for segment in &self.datas {
+            match &segment.kind {
+                DataKind::Active {
+                    memory_index: 0,
+                    init_expr,
+                } => {
+                    data.push(0x00);
+                    write_init_expr(&mut data, init_expr)?;
+                }
+                DataKind::Active {
+                    memory_index,
+                    init_expr,
+                } => {
+                    data.push(0x02);
+                    write_u32(&mut data, *memory_index);
+                    write_init_expr(&mut data, init_expr)?;
+                }
+                DataKind::Passive => data.push(0x01),
+            }
+            write_u32(&mut data, segment.data.len() as u32);
+            data.extend(segment.data);
+        }
+        write_section(&mut wasm, 11, self.datas.len(), data);
+
+        // the name section is optional, but makes disassemblies far easier to read
+        let mut names = vec![];
+        write_name(&mut names, "name");
+        let mut module = vec![];
+        write_name(&mut module, &self.names.module);
+        names.push(0x00);
+        write_u32(&mut names, module.len() as u32);
+        names.extend(module);
+
+        let mut functions: Vec<_> = self.names.functions.iter().collect();
+        functions.sort_by_key(|(index, _)| **index);
+        let mut map = vec![];
+        write_u32(&mut map, functions.len() as u32);
+        for (index, name) in functions {
+            write_u32(&mut map, *index);
+            write_name(&mut map, name);
+        }
+        names.push(0x01);
+        write_u32(&mut names, map.len() as u32);
+        names.extend(map);
+
+        write_raw_section(&mut wasm, 0, names);
+        Ok(wasm)
+    }
+}
+
+/// Writes a section whose contents are a vector of `count` items, omitting it when empty.
+fn write_section(out: &mut Vec<u8>, id: u8, count: usize, items: Vec<u8>) {
+    if count > 0 {
+        let mut data = vec![];
+        write_u32(&mut data, count as u32);
+        data.extend(items);
+        write_raw_section(out, id, data);
+    }
+}
+
+fn write_raw_section(out: &mut Vec<u8>, id: u8, data: Vec<u8>) {
+    out.push(id);
+    write_u32(out, data.len() as u32);
+    out.extend(data);
+}
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    write_u64(out, value.into())
+}
+
+fn write_u64(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.push(byte);
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_i64(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            return out.push(byte);
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    write_u32(out, name.len() as u32);
+    out.extend(name.as_bytes());
+}
+
+fn write_limits(out: &mut Vec<u8>, min: u64, max: Option<u64>, shared: bool) {
+    let flags = u8::from(max.is_some()) | (u8::from(shared) << 1);
+    out.push(flags);
+    write_u64(out, min);
+    if let Some(max) = max {
+        write_u64(out, max);
+    }
+}
+
+fn write_init_expr(out: &mut Vec<u8>, expr: &InitExpr) -> Result<()> {
+    let mut reader = expr.get_binary_reader();
+    out.extend(reader.read_bytes(reader.bytes_remaining())?);
+    Ok(())
+}
+
+fn write_const(out: &mut Vec<u8>, value: Value) -> Result<()> {
+    match value {
+        Value::I32(x) => write_op(out, &Operator::I32Const { value: x as i32 })?,
+        Value::I64(x) => write_op(out, &Operator::I64Const { value: x as i64 })?,
+        Value::F32(x) => {
+            out.push(0x43);
+            out.extend(x.to_bits().to_le_bytes());
+        }
+        Value::F64(x) => {
+            out.push(0x44);
+            out.extend(x.to_bits().to_le_bytes());
+        }
+        Value::RefNull => out.extend([0xd0, 0x70]),
+        Value::FuncRef(index) => write_op(
+            out,
+            &Operator::RefFunc {
+                function_index: index,
+            },
+        )?,
+        Value::InternalRef(_) => bail!("internal references have no wasm encoding"),
+    }
+    out.push(0x0b);
+    Ok(())
+}
+
+fn type_byte(ty: Type) -> Result<u8> {
+    Ok(match ty {
+        Type::I32 => 0x7f,
+        Type::I64 => 0x7e,
+        Type::F32 => 0x7d,
+        Type::F64 => 0x7c,
+        Type::V128 => 0x7b,
+        Type::FuncRef => 0x70,
+        Type::ExternRef => 0x6f,
+        Type::EmptyBlockType => 0x40,
+        ty => bail!("type {ty:?} has no wasm encoding"),
+    })
+}
+
+fn arb_type_byte(ty: ArbValueType) -> u8 {
+    type_byte(ty.into()).expect("value types are always encodable")
+}
+
+fn write_memarg(out: &mut Vec<u8>, memarg: &MemoryImmediate) -> Result<()> {
+    ensure!(memarg.memory == 0, "multiple memories are not supported");
+    write_u32(out, memarg.align.into());
+    write_u64(out, memarg.offset);
+    Ok(())
+}
+
+fn write_op(out: &mut Vec<u8>, op: &Operator) -> Result<()> {
+    use Operator::*;
+
+    let code = usize::from(OperatorCode::from(op));
+    match code >> 8 {
+        0 => out.push(code as u8),
+        0xfc => {
+            out.push(0xfc);
+            write_u32(out, code as u32 & 0xff);
+        }
+        _ => bail!("operator {op:?} is not supported"),
+    }
+
+    match op {
+        Block { ty } | Loop { ty } | If { ty } | Try { ty } => match ty {
+            TypeOrFuncType::Type(ty) => out.push(type_byte(*ty)?),
+            TypeOrFuncType::FuncType(index) => write_i64(out, (*index).into()),
+        },
+        Br { relative_depth }
+        | BrIf { relative_depth }
+        | Rethrow { relative_depth }
+        | Delegate { relative_depth } => write_u32(out, *relative_depth),
+        BrTable { table } => {
+            write_u32(out, table.len());
+            for target in table.targets() {
+                write_u32(out, target?);
+            }
+            write_u32(out, table.default());
+        }
+        Call { function_index } | ReturnCall { function_index } | RefFunc { function_index } => {
+            write_u32(out, *function_index)
+        }
+        CallIndirect { index, table_index } | ReturnCallIndirect { index, table_index } => {
+            write_u32(out, *index);
+            write_u32(out, *table_index);
+        }
+        Catch { index } | Throw { index } => write_u32(out, *index),
+        TypedSelect { ty } => {
+            write_u32(out, 1);
+            out.push(type_byte(*ty)?);
+        }
+        LocalGet { local_index } | LocalSet { local_index } | LocalTee { local_index } => {
+            write_u32(out, *local_index)
+        }
+        GlobalGet { global_index } | GlobalSet { global_index } => write_u32(out, *global_index),
+        I32Load { memarg }
+        | I64Load { memarg }
+        | F32Load { memarg }
+        | F64Load { memarg }
+        | I32Load8S { memarg }
+        | I32Load8U { memarg }
+        | I32Load16S { memarg }
+        | I32Load16U { memarg }
+        | I64Load8S { memarg }
+        | I64Load8U { memarg }
+        | I64Load16S { memarg }
+        | I64Load16U { memarg }
+        | I64Load32S { memarg }
+        | I64Load32U { memarg }
+        | I32Store { memarg }
+        | I64Store { memarg }
+        | F32Store { memarg }
+        | F64Store { memarg }
+        | I32Store8 { memarg }
+        | I32Store16 { memarg }
+        | I64Store8 { memarg }
+        | I64Store16 { memarg }
+        | I64Store32 { memarg } => write_memarg(out, memarg)?,
+        MemorySize { mem, .. } | MemoryGrow { mem, .. } | MemoryFill { mem } => {
+            write_u32(out, *mem)
+        }
+        I32Const { value } => write_i64(out, (*value).into()),
+        I64Const { value } => write_i64(out, *value),
+        F32Const { value } => out.extend(value.bits().to_le_bytes()),
+        F64Const { value } => out.extend(value.bits().to_le_bytes()),
+        RefNull { ty } => out.push(type_byte(*ty)?),
+        MemoryInit { segment, mem } => {
+            write_u32(out, *segment);
+            write_u32(out, *mem);
+        }
+        DataDrop { segment } | ElemDrop { segment } => write_u32(out, *segment),
+        MemoryCopy { src, dst } => {
+            write_u32(out, *dst);
+            write_u32(out, *src);
+        }
+        TableInit { segment, table } => {
+            write_u32(out, *segment);
+            write_u32(out, *table);
+        }
+        TableCopy {
+            dst_table,
+            src_table,
+        } => {
+            write_u32(out, *dst_table);
+            write_u32(out, *src_table);
+        }
+        TableFill { table }
+        | TableGet { table }
+        | TableSet { table }
+        | TableGrow { table }
+        | TableSize { table } => write_u32(out, *table),
+        _ => {}
+    }
+    Ok(())
+}
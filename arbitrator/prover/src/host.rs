@@ -31,6 +31,7 @@ pub enum InternalFunc {
     UserSetInk,
     UserStackLeft,
     UserSetStack,
+    UserMemorySize,
 }
 
 impl InternalFunc {
@@ -47,11 +48,12 @@ impl InternalFunc {
             WavmCallerLoad8  | WavmCallerLoad32  => func!([I32], [I32]),
             WavmCallerStore8 | WavmCallerStore32 => func!([I32, I32], []),
             MemoryFill       | MemoryCopy        => func!([I32, I32, I32], []),
-            UserInkLeft   => func!([], [I64]),      // λ() → ink_left
-            UserInkStatus => func!([], [I32]),      // λ() → ink_status
-            UserSetInk    => func!([I64, I32], []), // λ(ink_left, ink_status)
-            UserStackLeft => func!([], [I32]),      // λ() → stack_left
-            UserSetStack  => func!([I32], []),      // λ(stack_left)
+            UserInkLeft    => func!([], [I64]),      // λ() → ink_left
+            UserInkStatus  => func!([], [I32]),      // λ() → ink_status
+            UserSetInk     => func!([I64, I32], []), // λ(ink_left, ink_status)
+            UserStackLeft  => func!([], [I32]),      // λ() → stack_left
+            UserSetStack   => func!([I32], []),      // λ(stack_left)
+            UserMemorySize => func!([], [I32]),      // λ() → pages
         };
         ty
     }
@@ -80,6 +82,8 @@ pub enum Hostio {
     ProgramSetStack,
     ProgramCallMain,
     ConsoleLogTxt,
+    ConsoleLogBytes,
+    ConsoleLogLeveled,
     ConsoleLogI32,
     ConsoleLogI64,
     ConsoleLogF32,
@@ -91,6 +95,7 @@ pub enum Hostio {
     UserInkLeft,
     UserInkStatus,
     UserSetInk,
+    UserMemorySize,
 }
 
 impl FromStr for Hostio {
@@ -124,7 +129,10 @@ impl FromStr for Hostio {
             ("hostio", "user_ink_left") => UserInkLeft,
             ("hostio", "user_ink_status") => UserInkStatus,
             ("hostio", "user_set_ink") => UserSetInk,
+            ("hostio", "user_memory_size") => UserMemorySize,
             ("console", "log_txt") => ConsoleLogTxt,
+            ("console", "log_bytes") => ConsoleLogBytes,
+            ("console", "log_leveled") => ConsoleLogLeveled,
             ("console", "log_i32") => ConsoleLogI32,
             ("console", "log_i64") => ConsoleLogI64,
             ("console", "log_f32") => ConsoleLogF32,
@@ -178,6 +186,8 @@ impl Hostio {
             ProgramSetStack             => func!([I32, I32, I32]),        // λ(module, internals, stack_left)
             ProgramCallMain             => func!([I32, I32, I32], [I32]), // λ(module, main, args_len) → status
             ConsoleLogTxt               => func!([I32, I32]),             // λ(text, len)
+            ConsoleLogBytes             => func!([I32, I32]),             // λ(data, len)
+            ConsoleLogLeveled           => func!([I32, I32, I32]),        // λ(level, text, len)
             ConsoleLogI32               => func!([I32]),                  // λ(value)
             ConsoleLogI64               => func!([I64]),                  // λ(value)
             ConsoleLogF32               => func!([F32]),                  // λ(value)
@@ -189,6 +199,7 @@ impl Hostio {
             UserInkLeft                 => InternalFunc::UserInkLeft.ty(),
             UserInkStatus               => InternalFunc::UserInkStatus.ty(),
             UserSetInk                  => InternalFunc::UserSetInk.ty(),
+            UserMemorySize              => InternalFunc::UserMemorySize.ty(),
         };
         ty
     }
@@ -339,7 +350,12 @@ impl Hostio {
                 opcode!(LocalGet, 1);
                 intern!(UserSetInk);
             }
-            ConsoleLogTxt | ConsoleLogI32 | ConsoleLogI64 | ConsoleLogF32 | ConsoleLogF64 => {}
+            UserMemorySize => {
+                // λ() → pages
+                intern!(UserMemorySize);
+            }
+            ConsoleLogTxt | ConsoleLogBytes | ConsoleLogLeveled | ConsoleLogI32 | ConsoleLogI64
+            | ConsoleLogF32 | ConsoleLogF64 => {}
             ConsoleTeeI32 | ConsoleTeeI64 | ConsoleTeeF32 | ConsoleTeeF64 => {
                 opcode!(LocalGet, 0);
             }
@@ -425,6 +441,7 @@ pub fn new_internal_funcs(globals: Option<StylusData>) -> Vec<Function> {
         );
         add_func(&[Instruction::with_data(GlobalGet, depth)], UserStackLeft);
         add_func(&[Instruction::with_data(GlobalSet, depth)], UserSetStack);
+        add_func(&[Instruction::simple(MemorySize)], UserMemorySize);
     }
     funcs
 }
@@ -4,6 +4,7 @@
 #![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
 
 pub mod binary;
+pub mod encode;
 mod host;
 pub mod machine;
 /// cbindgen:ignore
@@ -1051,8 +1051,9 @@ impl Machine {
     pub fn from_user_path(path: &Path, compile: &CompileConfig) -> Result<Self> {
         let data = std::fs::read(path)?;
         let wasm = wasmer::wat2wasm(&data)?;
-        let mut bin = binary::parse(&wasm, Path::new("user"))?;
+        let mut bin = binary::parse_with_limits(&wasm, Path::new("user"), &compile.limits)?;
         let stylus_data = bin.instrument(compile)?;
+        let footprint: u32 = stylus_data.footprint.into();
 
         let user_test = std::fs::read("../../target/machines/latest/user_test.wasm")?;
         let user_test = parse(&user_test, Path::new("user_test"))?;
@@ -1074,8 +1075,6 @@ impl Machine {
             Arc::new(|_, _| panic!("tried to read preimage")),
             Some(stylus_data),
         )?;
-
-        let footprint: u32 = stylus_data.footprint.into();
         machine.call_function("user_test", "set_pages", vec![footprint.into()])?;
         Ok(machine)
     }
@@ -1126,8 +1125,8 @@ impl Machine {
         debug_funcs: bool,
         hash: Option<Bytes32>,
     ) -> Result<Bytes32> {
-        let mut bin = binary::parse(wasm, Path::new("user"))?;
         let config = CompileConfig::version(version, debug_funcs);
+        let mut bin = binary::parse_with_limits(wasm, Path::new("user"), &config.limits)?;
         let stylus_data = bin.instrument(&config)?;
 
         let forward = include_bytes!("../../../target/machines/latest/forward_stub.wasm");
@@ -1768,6 +1767,16 @@ impl Machine {
         Some(self.pc)
     }
 
+    /// The instruction that caused the machine to error, if it has.
+    pub fn error_pc(&self) -> Option<ProgramCounter> {
+        if self.status != MachineStatus::Errored {
+            return None;
+        }
+        let mut pc = self.pc;
+        pc.inst = pc.inst.saturating_sub(1); // the pc advances before each instruction executes
+        Some(pc)
+    }
+
     fn test_next_instruction(func: &Function, pc: &ProgramCounter) {
         let inst: usize = pc.inst.try_into().unwrap();
         debug_assert!(func.code.len() > inst);
@@ -2517,6 +2526,21 @@ impl Machine {
                 }
                 Ok(())
             }
+            ("console", "log_leveled") => {
+                let level = pull_arg!(2, I32);
+                let ptr = pull_arg!(1, I32);
+                let len = pull_arg!(0, I32);
+                let text = String::from_utf8_lossy(read_bytes_segment!(ptr, len));
+                Self::say(format!("[{level}] {text}"));
+                Ok(())
+            }
+            ("console", "log_bytes") => {
+                let ptr = pull_arg!(1, I32);
+                let len = pull_arg!(0, I32);
+                let data = read_bytes_segment!(ptr, len);
+                Self::say(format!("0x{}", hex::encode(data)));
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
@@ -3,9 +3,15 @@
 
 #![allow(clippy::field_reassign_with_default)]
 
-use crate::{programs::meter, value::FunctionType};
+use crate::{
+    programs::meter::{self, ConfigPricer, CostTable, CostTableParams},
+    value::FunctionType,
+};
+use arbutil::{crypto, Bytes32, Color};
 use derivative::Derivative;
+use eyre::{bail, Result};
 use fnv::FnvHashMap as HashMap;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use wasmer_types::{Pages, SignatureIndex, WASM_PAGE_SIZE};
 use wasmparser::Operator;
@@ -13,15 +19,19 @@ use wasmparser::Operator;
 #[cfg(feature = "native")]
 use {
     super::{
-        counter::Counter, depth::DepthChecker, dynamic::DynamicMeter, heap::HeapBound,
-        meter::Meter, start::StartMover, MiddlewareWrapper,
+        counter::Counter, depth::DepthChecker, dynamic::DynamicMeter, float::NoFloat,
+        fuel::FuelChecker, grow::GrowMeter, heap::HeapBound, meter::Meter, start::StartMover,
+        MiddlewareWrapper,
     },
     std::sync::Arc,
-    wasmer::{Cranelift, CraneliftOptLevel, Store},
+    wasmer::Store,
     wasmer_compiler_singlepass::Singlepass,
 };
 
-#[derive(Clone, Copy, Debug)]
+#[cfg(all(feature = "native", feature = "cranelift"))]
+use wasmer::{Cranelift, CraneliftOptLevel};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(C)]
 pub struct StylusConfig {
     /// Version the program was compiled against
@@ -32,7 +42,7 @@ pub struct StylusConfig {
     pub pricing: PricingParams,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(C)]
 pub struct PricingParams {
     /// The price of ink, measured in bips of an evm gas
@@ -72,13 +82,38 @@ impl PricingParams {
         Self { ink_price }
     }
 
+    /// Converts gas to ink, saturating on overflow.
     pub fn gas_to_ink(&self, gas: u64) -> u64 {
         gas.saturating_mul(self.ink_price.into())
     }
 
+    /// Converts ink to gas, rounding down so that leftover ink never refunds extra gas.
     pub fn ink_to_gas(&self, ink: u64) -> u64 {
         ink / self.ink_price as u64 // never 0
     }
+
+    /// Converts gas to ink, erring rather than saturating on overflow.
+    pub fn try_gas_to_ink(&self, gas: u64) -> Result<u64> {
+        if self.ink_price == 0 {
+            bail!("ink price is zero");
+        }
+        match gas.checked_mul(self.ink_price.into()) {
+            Some(ink) => Ok(ink),
+            None => bail!(
+                "{} gas overflows at ink price {}",
+                gas.red(),
+                self.ink_price.red()
+            ),
+        }
+    }
+
+    /// Converts ink to gas, rounding down. Errs when the ink price is zero.
+    pub fn try_ink_to_gas(&self, ink: u64) -> Result<u64> {
+        if self.ink_price == 0 {
+            bail!("ink price is zero");
+        }
+        Ok(ink / self.ink_price as u64)
+    }
 }
 
 pub type SigMap = HashMap<SignatureIndex, FunctionType>;
@@ -92,6 +127,10 @@ pub struct CompileConfig {
     pub pricing: CompilePricingParams,
     /// Memory bounds
     pub bounds: CompileMemoryParams,
+    /// Anti-DoS limits enforced while parsing
+    pub limits: CompileParseLimits,
+    /// Whether to reject programs that use floating-point operators
+    pub forbid_floats: bool,
     /// Debug parameters for test chains
     pub debug: CompileDebugParams,
 }
@@ -100,40 +139,153 @@ pub struct CompileConfig {
 pub struct CompileMemoryParams {
     /// The maximum number of pages a program may start with
     pub heap_bound: Pages,
+    /// The maximum number of pages a program may grow its memory to, if bounded
+    pub grow_bound: Option<Pages>,
     /// The maximum size of a stack frame, measured in words
     pub max_frame_size: u32,
     /// The maximum number of overlapping value lifetimes in a frame
     pub max_frame_contention: u16,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompileParseLimits {
+    /// The maximum number of functions a program may define
+    pub max_functions: u32,
+    /// The maximum number of types a program may declare
+    pub max_types: u32,
+    /// The maximum number of imports a program may declare
+    pub max_imports: u32,
+    /// The maximum number of globals a program may define
+    pub max_globals: u32,
+    /// The maximum number of data segments a program may declare
+    pub max_data_segments: u32,
+    /// The maximum number of element segments a program may declare
+    pub max_element_segments: u32,
+}
+
 #[derive(Clone, Derivative)]
 #[derivative(Debug)]
 pub struct CompilePricingParams {
     /// Associates opcodes to their ink costs
     #[derivative(Debug = "ignore")]
     pub costs: OpCosts,
+    /// Associates opcodes to their ink costs via data, overriding `costs` when set
+    pub cost_table: Option<CostTable>,
     /// Per-byte `MemoryFill` cost
     pub memory_fill_ink: u64,
     /// Per-byte `MemoryCopy` cost
     pub memory_copy_ink: u64,
+    /// Per-page `MemoryGrow` cost, at most `MAX_MEMORY_GROW_INK`
+    pub memory_grow_ink: u64,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default)]
 pub struct CompileDebugParams {
     /// Allow debug functions
     pub debug_funcs: bool,
     /// Add instrumentation to count the number of times each kind of opcode is executed
     pub count_ops: bool,
-    /// Whether to use the Cranelift compiler
-    pub cranelift: bool,
+    /// The compiler to produce native code with. Only honored alongside `debug_funcs`.
+    pub backend: CompilerBackend,
+    /// Make opcodes free to execute. Only honored alongside `debug_funcs`.
+    pub free_ink: bool,
+    /// Skip the compiler's verifier, which is only safe for modules already validated.
+    /// Only honored alongside `debug_funcs`.
+    pub skip_verifier: bool,
+    /// Leave NaNs as the hardware produces them, which is nondeterministic and unfit for consensus.
+    /// Only honored alongside `debug_funcs`.
+    pub skip_nan_canonicalization: bool,
+    /// Consult the fuel checkpoint upon entering each function and loop.
+    /// Only honored alongside `debug_funcs`, and only by native modules.
+    pub fuel_checks: bool,
+}
+
+/// The wasmer compiler that turns instrumented modules into native code.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompilerBackend {
+    /// The linear-time compiler consensus relies on, whose output is resistant to compiler bombs
+    #[default]
+    Singlepass,
+    /// An optimizing compiler for faster local simulation, which meters identically.
+    /// Only honored in builds with the `cranelift` feature.
+    Cranelift,
+}
+
+/// The serializable parts of a `CompileConfig`. The opcode pricing function is determined by the
+/// version, though a cost table overriding it is carried along. Every non-optional field is
+/// required when deserializing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompileConfigParams {
+    /// Version of the compiler to use
+    pub version: u16,
+    /// The maximum number of pages a program may start with
+    pub heap_bound: u32,
+    /// The maximum number of pages a program may grow its memory to, if bounded
+    pub grow_bound: Option<u32>,
+    /// The maximum size of a stack frame, measured in words
+    pub max_frame_size: u32,
+    /// The maximum number of overlapping value lifetimes in a frame
+    pub max_frame_contention: u16,
+    /// The maximum number of functions a program may define
+    pub max_functions: u32,
+    /// The maximum number of types a program may declare
+    pub max_types: u32,
+    /// The maximum number of imports a program may declare
+    pub max_imports: u32,
+    /// The maximum number of globals a program may define
+    pub max_globals: u32,
+    /// The maximum number of data segments a program may declare
+    pub max_data_segments: u32,
+    /// The maximum number of element segments a program may declare
+    pub max_element_segments: u32,
+    /// Per-byte `MemoryFill` cost
+    pub memory_fill_ink: u64,
+    /// Per-byte `MemoryCopy` cost
+    pub memory_copy_ink: u64,
+    /// Per-page `MemoryGrow` cost
+    pub memory_grow_ink: u64,
+    /// Associates opcodes to their ink costs via data, overriding the version's pricing when set
+    pub cost_table: Option<CostTableParams>,
+    /// Whether to reject programs that use floating-point operators
+    pub forbid_floats: bool,
+    /// Allow debug functions
+    pub debug_funcs: bool,
+    /// Add instrumentation to count the number of times each kind of opcode is executed
+    pub count_ops: bool,
+    /// The compiler to produce native code with
+    pub backend: CompilerBackend,
+    /// Make opcodes free to execute. Only honored alongside `debug_funcs`.
+    pub free_ink: bool,
+    /// Skip the compiler's verifier. Only honored alongside `debug_funcs`.
+    pub skip_verifier: bool,
+    /// Leave NaNs as the hardware produces them. Only honored alongside `debug_funcs`.
+    pub skip_nan_canonicalization: bool,
+    /// Consult the fuel checkpoint upon entering each function and loop
+    pub fuel_checks: bool,
+}
+
+impl CompilePricingParams {
+    /// The largest per-page `MemoryGrow` cost, which keeps the product with any
+    /// `u32` page count within a `u64`.
+    pub const MAX_MEMORY_GROW_INK: u64 = u32::MAX as u64;
+
+    /// The opcode pricer to meter with, preferring the cost table when one is set.
+    pub fn pricer(&self) -> ConfigPricer {
+        match &self.cost_table {
+            Some(table) => ConfigPricer::Table(table.clone()),
+            None => ConfigPricer::Func(self.costs),
+        }
+    }
 }
 
 impl Default for CompilePricingParams {
     fn default() -> Self {
         Self {
             costs: |_, _| 0,
+            cost_table: None,
             memory_fill_ink: 0,
             memory_copy_ink: 0,
+            memory_grow_ink: 0,
         }
     }
 }
@@ -142,14 +294,54 @@ impl Default for CompileMemoryParams {
     fn default() -> Self {
         Self {
             heap_bound: Pages(u32::MAX / WASM_PAGE_SIZE as u32),
+            grow_bound: None,
             max_frame_size: u32::MAX,
             max_frame_contention: u16::MAX,
         }
     }
 }
 
+impl Default for CompileParseLimits {
+    fn default() -> Self {
+        Self {
+            max_functions: 250_000,
+            max_types: 50_000,
+            max_imports: 50_000,
+            max_globals: 250_000,
+            max_data_segments: 50_000,
+            max_element_segments: 50_000,
+        }
+    }
+}
+
+impl CompileMemoryParams {
+    pub fn new(heap_bound: Pages, max_frame_size: u32, max_frame_contention: u16) -> Result<Self> {
+        if max_frame_size == 0 {
+            bail!("max frame size must be nonzero");
+        }
+        if max_frame_contention == 0 {
+            bail!("max frame contention must be nonzero");
+        }
+        if heap_bound > Pages::max_value() {
+            bail!("heap bound {} exceeds the wasm limit", heap_bound.0.red());
+        }
+        Ok(Self {
+            heap_bound,
+            grow_bound: None,
+            max_frame_size,
+            max_frame_contention,
+        })
+    }
+}
+
 impl CompileConfig {
+    /// The preset for the given version. Panics if no config exists for it.
     pub fn version(version: u16, debug_chain: bool) -> Self {
+        Self::try_version(version, debug_chain).unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// The preset for the given version, erring if no config exists for it.
+    pub fn try_version(version: u16, debug_chain: bool) -> Result<Self> {
         let mut config = Self::default();
         config.version = version;
         config.debug.debug_funcs = debug_chain;
@@ -158,44 +350,156 @@ impl CompileConfig {
             0 => {}
             1 => {
                 // TODO: settle on reasonable values for the v1 release
-                config.bounds.heap_bound = Pages(128); // 8 mb
-                config.bounds.max_frame_size = 10 * 1024;
-                config.bounds.max_frame_contention = 4096;
+                let bounds = CompileMemoryParams::new(Pages(128), 10 * 1024, 4096); // 8 mb
+                config.bounds = bounds.expect("invalid v1 memory params");
+                config.bounds.grow_bound = Some(Pages(128));
                 config.pricing = CompilePricingParams {
                     costs: meter::pricing_v1,
+                    cost_table: None,
                     memory_fill_ink: 1000 / 8,
                     memory_copy_ink: 1000 / 8,
+                    memory_grow_ink: 0, // charged by the memory_grow hostio
                 };
             }
-            _ => panic!("no config exists for Stylus version {version}"),
+            _ => bail!("no config exists for Stylus version {}", version.red()),
         }
 
-        config
+        Ok(config)
     }
 
+    /// Reconstructs a config from its serializable parts, deriving the pricer from the version.
+    /// Errs if no config exists for the version or a pricing parameter is malformed.
+    pub fn from_params(params: CompileConfigParams) -> Result<Self> {
+        let grow_ink = params.memory_grow_ink;
+        if grow_ink > CompilePricingParams::MAX_MEMORY_GROW_INK {
+            bail!("memory grow ink {} is too large", grow_ink.red());
+        }
+        let mut config = Self::try_version(params.version, params.debug_funcs)?;
+        config.bounds.heap_bound = Pages(params.heap_bound);
+        config.bounds.grow_bound = params.grow_bound.map(Pages);
+        config.bounds.max_frame_size = params.max_frame_size;
+        config.bounds.max_frame_contention = params.max_frame_contention;
+        config.limits.max_functions = params.max_functions;
+        config.limits.max_types = params.max_types;
+        config.limits.max_imports = params.max_imports;
+        config.limits.max_globals = params.max_globals;
+        config.limits.max_data_segments = params.max_data_segments;
+        config.limits.max_element_segments = params.max_element_segments;
+        config.pricing.memory_fill_ink = params.memory_fill_ink;
+        config.pricing.memory_copy_ink = params.memory_copy_ink;
+        config.pricing.memory_grow_ink = params.memory_grow_ink;
+        config.pricing.cost_table = params.cost_table.map(CostTable::from_params).transpose()?;
+        config.forbid_floats = params.forbid_floats;
+        config.debug.count_ops = params.count_ops;
+        config.debug.backend = params.backend;
+        config.debug.free_ink = params.free_ink;
+        config.debug.skip_verifier = params.skip_verifier;
+        config.debug.skip_nan_canonicalization = params.skip_nan_canonicalization;
+        config.debug.fuel_checks = params.fuel_checks;
+        Ok(config)
+    }
+
+    /// Extracts the serializable parts of the config.
+    pub fn to_params(&self) -> CompileConfigParams {
+        CompileConfigParams {
+            version: self.version,
+            heap_bound: self.bounds.heap_bound.0,
+            grow_bound: self.bounds.grow_bound.map(|pages| pages.0),
+            max_frame_size: self.bounds.max_frame_size,
+            max_frame_contention: self.bounds.max_frame_contention,
+            max_functions: self.limits.max_functions,
+            max_types: self.limits.max_types,
+            max_imports: self.limits.max_imports,
+            max_globals: self.limits.max_globals,
+            max_data_segments: self.limits.max_data_segments,
+            max_element_segments: self.limits.max_element_segments,
+            memory_fill_ink: self.pricing.memory_fill_ink,
+            memory_copy_ink: self.pricing.memory_copy_ink,
+            memory_grow_ink: self.pricing.memory_grow_ink,
+            cost_table: self.pricing.cost_table.as_ref().map(CostTable::to_params),
+            forbid_floats: self.forbid_floats,
+            debug_funcs: self.debug.debug_funcs,
+            count_ops: self.debug.count_ops,
+            backend: self.debug.backend,
+            free_ink: self.debug.free_ink,
+            skip_verifier: self.debug.skip_verifier,
+            skip_nan_canonicalization: self.debug.skip_nan_canonicalization,
+            fuel_checks: self.debug.fuel_checks,
+        }
+    }
+
+    /// Identifies the instrumentation this config produces, so that modules compiled under one
+    /// config are never run under another. Differs whenever any parameter or the cost table does.
+    pub fn fingerprint(&self) -> Bytes32 {
+        let mut data = b"stylus compile config".to_vec();
+        data.extend(bincode::serialize(&self.to_params()).expect("failed to serialize params"));
+        crypto::keccak(data).into()
+    }
+
+    /// The compiler to use, which is always `Singlepass` outside of debug chains
+    /// and in builds lacking the `cranelift` feature.
+    pub fn backend(&self) -> CompilerBackend {
+        match self.debug.debug_funcs && cfg!(feature = "cranelift") {
+            true => self.debug.backend,
+            false => CompilerBackend::Singlepass,
+        }
+    }
+
+    /// The pricing to instrument with, which is free when debugging with `free_ink`.
+    /// Hostios are still charged.
+    pub fn metered_pricing(&self) -> CompilePricingParams {
+        match self.debug.free_ink && self.debug.debug_funcs {
+            true => CompilePricingParams::default(),
+            false => self.pricing.clone(),
+        }
+    }
+
+    /// Whether the compiler should verify what it produces, which debug chains may skip.
+    pub fn verify(&self) -> bool {
+        !(self.debug.debug_funcs && self.debug.skip_verifier)
+    }
+
+    /// Whether the compiler should canonicalize NaNs, which debug chains may skip.
+    pub fn canonicalize_nans(&self) -> bool {
+        !(self.debug.debug_funcs && self.debug.skip_nan_canonicalization)
+    }
+
+    /// Creates a store that instruments modules according to the config.
+    /// The compiler verifies everything it produces unless a debug chain sets `skip_verifier`.
     #[cfg(feature = "native")]
     pub fn store(&self) -> Store {
-        let mut compiler: Box<dyn wasmer::CompilerConfig> = match self.debug.cranelift {
-            true => {
+        let mut compiler: Box<dyn wasmer::CompilerConfig> = match self.backend() {
+            CompilerBackend::Singlepass => Box::new(Singlepass::new()),
+            #[cfg(feature = "cranelift")]
+            CompilerBackend::Cranelift => {
                 let mut compiler = Cranelift::new();
                 compiler.opt_level(CraneliftOptLevel::Speed);
                 Box::new(compiler)
             }
-            false => Box::new(Singlepass::new()),
+            #[cfg(not(feature = "cranelift"))]
+            CompilerBackend::Cranelift => unreachable!("cranelift is unavailable in this build"),
         };
-        compiler.canonicalize_nans(true);
-        compiler.enable_verifier();
+        compiler.canonicalize_nans(self.canonicalize_nans());
+        if self.verify() {
+            compiler.enable_verifier();
+        }
 
-        let meter = MiddlewareWrapper::new(Meter::new(self.pricing.costs));
-        let dygas = MiddlewareWrapper::new(DynamicMeter::new(&self.pricing));
+        let pricing = self.metered_pricing();
+        let meter = MiddlewareWrapper::new(Meter::new(pricing.pricer()));
+        let dygas = MiddlewareWrapper::new(DynamicMeter::new(&pricing));
+        let grow = MiddlewareWrapper::new(GrowMeter::new(&pricing));
         let depth = MiddlewareWrapper::new(DepthChecker::new(self.bounds));
         let bound = MiddlewareWrapper::new(HeapBound::new(self.bounds));
         let start = MiddlewareWrapper::new(StartMover::default());
 
         // add the instrumentation in the order of application
         // note: this must be consistent with the prover
+        if self.forbid_floats {
+            compiler.push_middleware(Arc::new(MiddlewareWrapper::new(NoFloat::default())));
+        }
         compiler.push_middleware(Arc::new(meter));
         compiler.push_middleware(Arc::new(dygas));
+        compiler.push_middleware(Arc::new(grow));
         compiler.push_middleware(Arc::new(depth));
         compiler.push_middleware(Arc::new(bound));
         compiler.push_middleware(Arc::new(start));
@@ -205,10 +509,127 @@ impl CompileConfig {
             compiler.push_middleware(Arc::new(MiddlewareWrapper::new(counter)));
         }
 
+        // shifts function indices, so this must come last
+        if self.debug.fuel_checks && self.debug.debug_funcs {
+            let checker = FuelChecker::new();
+            compiler.push_middleware(Arc::new(MiddlewareWrapper::new(checker)));
+        }
+
         Store::new(compiler)
     }
 }
 
+/// Builds a matching pair of compile-time and runtime configs, checking that they agree.
+#[derive(Clone, Debug, Default)]
+pub struct StylusConfigBuilder {
+    compile: CompileConfig,
+    config: StylusConfig,
+}
+
+impl StylusConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies the preset for the given version, keeping the debug settings.
+    pub fn version(mut self, version: u16) -> Self {
+        let debug = self.compile.debug;
+        self.compile = CompileConfig::version(version, debug.debug_funcs);
+        self.compile.debug = debug;
+        self.config.version = version;
+        self
+    }
+
+    pub fn heap_bound(mut self, heap_bound: Pages) -> Self {
+        self.compile.bounds.heap_bound = heap_bound;
+        self
+    }
+
+    pub fn grow_bound(mut self, grow_bound: Option<Pages>) -> Self {
+        self.compile.bounds.grow_bound = grow_bound;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.config.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_frame_size(mut self, max_frame_size: u32) -> Self {
+        self.compile.bounds.max_frame_size = max_frame_size;
+        self
+    }
+
+    pub fn max_frame_contention(mut self, max_frame_contention: u16) -> Self {
+        self.compile.bounds.max_frame_contention = max_frame_contention;
+        self
+    }
+
+    pub fn ink_price(mut self, ink_price: u32) -> Self {
+        self.config.pricing.ink_price = ink_price;
+        self
+    }
+
+    pub fn costs(mut self, costs: OpCosts) -> Self {
+        self.compile.pricing.costs = costs;
+        self
+    }
+
+    pub fn cost_table(mut self, table: CostTable) -> Self {
+        self.compile.pricing.cost_table = Some(table);
+        self
+    }
+
+    pub fn forbid_floats(mut self, forbid_floats: bool) -> Self {
+        self.compile.forbid_floats = forbid_floats;
+        self
+    }
+
+    pub fn debug_funcs(mut self, debug_funcs: bool) -> Self {
+        self.compile.debug.debug_funcs = debug_funcs;
+        self
+    }
+
+    pub fn count_ops(mut self, count_ops: bool) -> Self {
+        self.compile.debug.count_ops = count_ops;
+        self
+    }
+
+    pub fn backend(mut self, backend: CompilerBackend) -> Self {
+        self.compile.debug.backend = backend;
+        self
+    }
+
+    /// Skips the compiler's verifier on debug chains. Only safe for modules already validated.
+    pub fn skip_verifier(mut self, skip_verifier: bool) -> Self {
+        self.compile.debug.skip_verifier = skip_verifier;
+        self
+    }
+
+    pub fn build(self) -> Result<(CompileConfig, StylusConfig)> {
+        let compile = self.compile;
+        let config = self.config;
+        let bounds = compile.bounds;
+
+        if config.pricing.ink_price == 0 {
+            bail!("ink price must be nonzero");
+        }
+        if config.max_depth < bounds.max_frame_size {
+            bail!(
+                "max depth {} is smaller than the max frame size {}",
+                config.max_depth.red(),
+                bounds.max_frame_size.red(),
+            );
+        }
+        CompileMemoryParams::new(
+            bounds.heap_bound,
+            bounds.max_frame_size,
+            bounds.max_frame_contention,
+        )?;
+        Ok((compile, config))
+    }
+}
+
 /// Information about a wasm for pricing purposes.
 #[repr(C)]
 pub struct WasmPricingInfo {
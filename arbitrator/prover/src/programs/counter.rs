@@ -7,27 +7,25 @@ use crate::Machine;
 use arbutil::operator::{OperatorCode, OperatorInfo};
 use eyre::{eyre, Result};
 use fnv::FnvHashMap as HashMap;
-use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use std::collections::BTreeMap;
 use std::{clone::Clone, fmt::Debug, sync::Arc};
 use wasmer_types::{GlobalIndex, GlobalInit, LocalFunctionIndex, Type};
 use wasmparser::Operator;
 
-lazy_static! {
-    /// Assigns each operator a sequential offset
-    pub static ref OP_OFFSETS: Mutex<HashMap<OperatorCode, usize>> = Mutex::new(HashMap::default());
-}
-
 #[derive(Debug)]
 pub struct Counter {
-    /// Assigns each relative offset a global variable
+    /// Assigns each operator's dense index a global variable
     pub counters: Arc<Mutex<Vec<GlobalIndex>>>,
 }
 
 impl Counter {
+    /// The number of dense indices that may be counted. Stylus forbids SIMD and threads,
+    /// so only the core and 0xfc pages of operators can appear in an instrumented module.
+    pub const SLOTS: usize = 0x200;
+
     pub fn new() -> Self {
-        let counters = Arc::new(Mutex::new(Vec::with_capacity(OperatorCode::OPERATOR_COUNT)));
+        let counters = Arc::new(Mutex::new(Vec::with_capacity(Self::SLOTS)));
         Self { counters }
     }
 
@@ -50,7 +48,7 @@ where
 
     fn update_module(&self, module: &mut M) -> Result<()> {
         let mut counters = self.counters.lock();
-        for index in 0..OperatorCode::OPERATOR_COUNT {
+        for index in 0..Self::SLOTS {
             let zero_count = GlobalInit::I64Const(0);
             let global = module.add_global(&Self::global_name(index), Type::I64, zero_count)?;
             counters.push(global);
@@ -69,7 +67,7 @@ where
 
 #[derive(Debug)]
 pub struct FuncCounter<'a> {
-    /// Assigns each relative offset a global variable
+    /// Assigns each operator's dense index a global variable
     counters: Arc<Mutex<Vec<GlobalIndex>>>,
     /// Instructions of the current basic block
     block: Vec<Operator<'a>>,
@@ -117,11 +115,11 @@ impl<'a> FuncMiddleware<'a> for FuncCounter<'a> {
             }
 
             let counters = self.counters.lock();
-            let mut operators = OP_OFFSETS.lock();
             for (op, count) in increments {
-                let opslen = operators.len();
-                let offset = *operators.entry(op).or_insert(opslen);
-                let global = *counters.get(offset).ok_or_else(|| eyre!("no global"))?;
+                let offset = op.dense_index();
+                let global = *counters
+                    .get(offset)
+                    .ok_or_else(|| eyre!("no counter for {}", op))?;
                 out.extend(update(global.as_u32(), count));
             }
 
@@ -143,11 +141,11 @@ impl CountingMachine for Machine {
     fn operator_counts(&mut self) -> Result<BTreeMap<OperatorCode, u64>> {
         let mut counts = BTreeMap::new();
 
-        for (&op, &offset) in OP_OFFSETS.lock().iter() {
+        for offset in 0..Counter::SLOTS {
             let count = self.get_global(&Counter::global_name(offset))?;
             let count: u64 = count.try_into()?;
             if count != 0 {
-                counts.insert(op, count);
+                counts.insert(OperatorCode::from_dense_index(offset), count);
             }
         }
         Ok(counts)
@@ -167,6 +167,7 @@ impl<'a> FuncMiddleware<'a> for FuncDepthChecker<'a> {
         // We've reached the final instruction and can instrument the function as follows:
         //   - When entering, check that the stack has sufficient space and deduct the amount used
         //   - When returning, credit back the amount used
+        //   - When branching to the function's outermost label, which also returns, do the same
 
         let size = self.worst_case_depth()?;
         let global_index = self.global.as_u32();
@@ -204,6 +205,15 @@ impl<'a> FuncMiddleware<'a> for FuncDepthChecker<'a> {
                 GlobalSet { global_index },
             ])
         };
+        let charge = |out: &mut O| {
+            out.extend([
+                // space -= size
+                GlobalGet { global_index },
+                I32Const { value: size as i32 },
+                I32Sub,
+                GlobalSet { global_index },
+            ])
+        };
 
         // add an extraneous return instruction to the end to match Arbitrator
         let mut code = std::mem::take(&mut self.code);
@@ -211,10 +221,27 @@ impl<'a> FuncMiddleware<'a> for FuncDepthChecker<'a> {
         code.push(Return);
         code.push(last);
 
+        let mut scopes = 0;
         for op in code {
-            let exit = matches!(op, Return);
-            if exit {
-                reclaim(out);
+            match op {
+                Block { .. } | Loop { .. } | If { .. } => scopes += 1,
+                End => scopes -= 1,
+                _ => {}
+            }
+
+            // branches to the outermost label skip the final return, so they credit space too
+            let outermost = |depth: u32| depth as isize == scopes;
+            match op {
+                Return => reclaim(out),
+                Br { relative_depth } if outermost(relative_depth) => reclaim(out),
+                BrIf { relative_depth } if outermost(relative_depth) => {
+                    // the global ops leave the stack untouched, so the branch still sees its values
+                    reclaim(out);
+                    out.extend([op]);
+                    charge(out); // the branch wasn't taken
+                    continue;
+                }
+                _ => {}
             }
             out.extend([op]);
         }
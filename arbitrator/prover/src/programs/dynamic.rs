@@ -125,6 +125,8 @@ impl<'a> FuncMiddleware<'a> for FuncDynamicMeter {
                 If { ty: if_ty },
                 I32Const { value: 1 },
                 set!(status),
+                I64Const { value: 0 },
+                set!(ink),
                 Unreachable,
                 End,
 
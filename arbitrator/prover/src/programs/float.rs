@@ -0,0 +1,49 @@
+// Copyright 2023, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use super::{FuncMiddleware, Middleware, ModuleMod};
+use arbutil::{operator::OperatorCode, Color};
+use eyre::{bail, Result};
+use wasmer_types::LocalFunctionIndex;
+use wasmparser::Operator;
+
+/// Rejects any program containing floating-point operators, including conversions to and from floats.
+#[derive(Debug, Default)]
+pub struct NoFloat {}
+
+impl<M: ModuleMod> Middleware<M> for NoFloat {
+    type FM<'a> = FuncNoFloat;
+
+    fn update_module(&self, _: &mut M) -> Result<()> {
+        Ok(())
+    }
+
+    fn instrument<'a>(&self, _: LocalFunctionIndex) -> Result<Self::FM<'a>> {
+        Ok(FuncNoFloat)
+    }
+
+    fn name(&self) -> &'static str {
+        "no float"
+    }
+}
+
+#[derive(Debug)]
+pub struct FuncNoFloat;
+
+impl<'a> FuncMiddleware<'a> for FuncNoFloat {
+    fn feed<O>(&mut self, op: Operator<'a>, out: &mut O) -> Result<()>
+    where
+        O: Extend<Operator<'a>>,
+    {
+        let code = OperatorCode::from(&op);
+        if code.is_float() {
+            bail!("floating point operator {} is forbidden", code.red());
+        }
+        out.extend([op]);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "no float"
+    }
+}
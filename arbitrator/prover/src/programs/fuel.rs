@@ -0,0 +1,172 @@
+// Copyright 2022-2023, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use super::{FuncMiddleware, Middleware};
+use arbutil::Color;
+use eyre::{bail, Result};
+use parking_lot::RwLock;
+use wasmer::ExportIndex;
+use wasmer_types::{
+    entity::{EntityRef, PrimaryMap},
+    FunctionIndex, FunctionType, GlobalInit, ImportIndex, ImportKey, LocalFunctionIndex,
+    ModuleInfo, Type,
+};
+use wasmparser::Operator;
+
+pub const FUEL_CHECKPOINT_MODULE: &str = "debug";
+pub const FUEL_CHECKPOINT_FIELD: &str = "fuel_checkpoint";
+
+/// Calls the `fuel_checkpoint` hostio upon entering each function and loop, so that embedders
+/// may interrupt programs that never call any other hostio.
+///
+/// Since programs don't import the hostio themselves, this middleware adds it, shifting the index
+/// of every function defined by the module. Only native modules support this.
+/// It must be applied last so that the other middlewares only ever see the original indices.
+#[derive(Debug, Default)]
+pub struct FuelChecker {
+    /// The index of the added import
+    checkpoint: RwLock<Option<FunctionIndex>>,
+}
+
+impl FuelChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Maps an index from before the import was added to its index after.
+fn shift(func: FunctionIndex, checkpoint: FunctionIndex) -> FunctionIndex {
+    match func < checkpoint {
+        true => func,
+        false => FunctionIndex::new(func.index() + 1),
+    }
+}
+
+impl Middleware<ModuleInfo> for FuelChecker {
+    type FM<'a> = FuncFuelChecker;
+
+    fn update_module(&self, module: &mut ModuleInfo) -> Result<()> {
+        let checkpoint = FunctionIndex::new(module.num_imported_functions);
+        let shift = |func| shift(func, checkpoint);
+
+        let key = ImportKey {
+            module: FUEL_CHECKPOINT_MODULE.to_owned(),
+            field: FUEL_CHECKPOINT_FIELD.to_owned(),
+            import_idx: module.imports.len() as u32,
+        };
+        if module
+            .imports
+            .keys()
+            .any(|k| k.module == key.module && k.field == key.field)
+        {
+            bail!("wasm already imports {}", FUEL_CHECKPOINT_FIELD.red());
+        }
+
+        // the import goes right after the others, before every function the module defines
+        let empty: Vec<Type> = vec![];
+        let sig = module
+            .signatures
+            .push(FunctionType::new(empty.clone(), empty));
+        let mut sigs: Vec<_> = module.functions.values().cloned().collect();
+        sigs.insert(checkpoint.index(), sig);
+        module.functions = PrimaryMap::with_capacity(sigs.len());
+        for sig in sigs {
+            module.functions.push(sig);
+        }
+        module
+            .imports
+            .insert(key, ImportIndex::Function(checkpoint));
+        module.num_imported_functions += 1;
+
+        // update everything that refers to a function by index
+        for export in module.exports.values_mut() {
+            if let ExportIndex::Function(func) = export {
+                *func = shift(*func);
+            }
+        }
+        for init in &mut module.table_initializers {
+            init.elements
+                .iter_mut()
+                .for_each(|func| *func = shift(*func));
+        }
+        for elements in module.passive_elements.values_mut() {
+            elements.iter_mut().for_each(|func| *func = shift(*func));
+        }
+        for init in module.global_initializers.values_mut() {
+            if let GlobalInit::RefFunc(func) = init {
+                *func = shift(*func);
+            }
+        }
+        module.start_function = module.start_function.map(shift);
+        module.function_names = module
+            .function_names
+            .drain()
+            .map(|(func, name)| (shift(func), name))
+            .collect();
+
+        *self.checkpoint.write() = Some(checkpoint);
+        Ok(())
+    }
+
+    fn instrument<'a>(&self, _: LocalFunctionIndex) -> Result<Self::FM<'a>> {
+        let checkpoint = self.checkpoint.read().expect("no fuel checkpoint");
+        Ok(FuncFuelChecker::new(checkpoint))
+    }
+
+    fn name(&self) -> &'static str {
+        "fuel checker"
+    }
+}
+
+#[derive(Debug)]
+pub struct FuncFuelChecker {
+    /// The index of the `fuel_checkpoint` import
+    checkpoint: FunctionIndex,
+    /// Whether the function's entry has been instrumented
+    entered: bool,
+}
+
+impl FuncFuelChecker {
+    fn new(checkpoint: FunctionIndex) -> Self {
+        Self {
+            checkpoint,
+            entered: false,
+        }
+    }
+}
+
+impl<'a> FuncMiddleware<'a> for FuncFuelChecker {
+    fn feed<O>(&mut self, op: Operator<'a>, out: &mut O) -> Result<()>
+    where
+        O: Extend<Operator<'a>>,
+    {
+        use Operator::*;
+
+        let index = self.checkpoint;
+        let checkpoint = Call {
+            function_index: index.as_u32(),
+        };
+        let shift = |function_index| shift(FunctionIndex::from_u32(function_index), index);
+
+        if !self.entered {
+            self.entered = true;
+            out.extend([checkpoint.clone()]);
+        }
+
+        match op {
+            Call { function_index } => out.extend([Call {
+                function_index: shift(function_index).as_u32(),
+            }]),
+            RefFunc { function_index } => out.extend([RefFunc {
+                function_index: shift(function_index).as_u32(),
+            }]),
+            Loop { .. } => out.extend([op, checkpoint]),
+            _ => out.extend([op]),
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "fuel checker"
+    }
+}
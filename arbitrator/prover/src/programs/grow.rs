@@ -0,0 +1,156 @@
+// Copyright 2023, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use super::{
+    config::CompilePricingParams,
+    dynamic::SCRATCH_GLOBAL,
+    meter::{STYLUS_INK_LEFT, STYLUS_INK_STATUS},
+    FuncMiddleware, Middleware, ModuleMod,
+};
+use eyre::Result;
+use parking_lot::RwLock;
+use wasmer_types::{GlobalIndex, LocalFunctionIndex};
+use wasmparser::{Operator, Type as WpType, TypeOrFuncType};
+
+/// Charges ink in proportion to the number of pages requested by each `memory.grow`.
+/// When the delta is a constant the cost is computed statically, otherwise it's read off the stack.
+#[derive(Debug)]
+pub struct GrowMeter {
+    memory_grow: u64,
+    globals: RwLock<Option<[GlobalIndex; 3]>>,
+}
+
+impl GrowMeter {
+    pub fn new(pricing: &CompilePricingParams) -> Self {
+        let max = CompilePricingParams::MAX_MEMORY_GROW_INK;
+        Self {
+            memory_grow: pricing.memory_grow_ink.min(max),
+            globals: RwLock::default(),
+        }
+    }
+}
+
+impl<M: ModuleMod> Middleware<M> for GrowMeter {
+    type FM<'a> = FuncGrowMeter;
+
+    fn update_module(&self, module: &mut M) -> Result<()> {
+        let ink = module.get_global(STYLUS_INK_LEFT)?;
+        let status = module.get_global(STYLUS_INK_STATUS)?;
+        let scratch = module.get_global(SCRATCH_GLOBAL)?;
+        *self.globals.write() = Some([ink, status, scratch]);
+        Ok(())
+    }
+
+    fn instrument<'a>(&self, _: LocalFunctionIndex) -> Result<Self::FM<'a>> {
+        let globals = self.globals.read().expect("no globals");
+        Ok(FuncGrowMeter::new(self.memory_grow, globals))
+    }
+
+    fn name(&self) -> &'static str {
+        "grow meter"
+    }
+}
+
+#[derive(Debug)]
+pub struct FuncGrowMeter {
+    /// Ink charged per page
+    memory_grow: u64,
+    /// The page delta pushed by the previous operator, if it was a constant
+    last_const: Option<u32>,
+    globals: [GlobalIndex; 3],
+}
+
+impl FuncGrowMeter {
+    fn new(memory_grow: u64, globals: [GlobalIndex; 3]) -> Self {
+        Self {
+            memory_grow,
+            last_const: None,
+            globals,
+        }
+    }
+}
+
+impl<'a> FuncMiddleware<'a> for FuncGrowMeter {
+    fn feed<O>(&mut self, op: Operator<'a>, out: &mut O) -> Result<()>
+    where
+        O: Extend<Operator<'a>>,
+    {
+        use Operator::*;
+        macro_rules! get {
+            ($global:expr) => {
+                GlobalGet {
+                    global_index: $global,
+                }
+            };
+        }
+        macro_rules! set {
+            ($global:expr) => {
+                GlobalSet {
+                    global_index: $global,
+                }
+            };
+        }
+
+        let [ink, status, scratch] = self.globals.map(|x| x.as_u32());
+        let if_ty = TypeOrFuncType::Type(WpType::EmptyBlockType);
+        let last_const = self.last_const.take();
+
+        if !matches!(op, MemoryGrow { .. }) || self.memory_grow == 0 {
+            if let I32Const { value } = op {
+                self.last_const = Some(value as u32);
+            }
+            out.extend([op]);
+            return Ok(());
+        }
+
+        // [ink ink cost] → ink -= cost, detecting underflow
+        #[rustfmt::skip]
+        let charge = [
+            I64Sub,
+            set!(ink),
+            get!(ink),
+
+            // [old_ink, new_ink] → (old_ink < new_ink) (overflow detected)
+            I64LtU,
+            If { ty: if_ty },
+            I32Const { value: 1 },
+            set!(status),
+            I64Const { value: 0 },
+            set!(ink),
+            Unreachable,
+            End,
+        ];
+
+        match last_const {
+            Some(pages) => {
+                // [pages] → the cost is known statically
+                let cost = self.memory_grow.saturating_mul(pages.into());
+                out.extend([get!(ink), get!(ink), I64Const { value: cost as i64 }]);
+                out.extend(charge);
+            }
+            None => {
+                // [pages] → cost = pages * coefficient, leaving pages on the stack
+                // the coefficient is at most u32::MAX, so the product fits in a u64
+                out.extend([
+                    set!(scratch),
+                    get!(ink),
+                    get!(ink),
+                    get!(scratch),
+                    I64ExtendI32U,
+                    I64Const {
+                        value: self.memory_grow as i64,
+                    },
+                    I64Mul,
+                ]);
+                out.extend(charge);
+                out.extend([get!(scratch)]);
+            }
+        }
+        out.extend([op]);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "grow meter"
+    }
+}
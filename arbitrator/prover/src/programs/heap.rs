@@ -45,7 +45,16 @@ impl<M: ModuleMod> Middleware<M> for HeapBound {
         let lim = self.limit;
 
         if min > lim {
-            bail!("memory size {} exceeds bound {}", min.0.red(), lim.0.red());
+            let max = match max {
+                Some(max) => max.0.to_string(),
+                None => "unbounded".to_owned(),
+            };
+            bail!(
+                "program requires {} pages (declared max {}) but only {} are allowed",
+                min.0.red(),
+                max.red(),
+                lim.0.red(),
+            );
         }
         if max == Some(min) {
             return Ok(());
@@ -6,11 +6,16 @@ use crate::{
     value::FunctionType,
     Machine,
 };
-use arbutil::{evm, operator::OperatorInfo};
+use arbutil::{
+    evm,
+    operator::{OperatorCode, OperatorInfo},
+    Color,
+};
 use derivative::Derivative;
-use eyre::Result;
+use eyre::{bail, Result};
 use fnv::FnvHashMap as HashMap;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Debug, Display},
     sync::Arc,
@@ -18,14 +23,116 @@ use std::{
 use wasmer_types::{GlobalIndex, GlobalInit, LocalFunctionIndex, SignatureIndex, Type};
 use wasmparser::{Operator, Type as WpType, TypeOrFuncType};
 
-use super::config::SigMap;
+use super::config::{OpCosts, SigMap};
 
 pub const STYLUS_INK_LEFT: &str = "stylus_ink_left";
 pub const STYLUS_INK_STATUS: &str = "stylus_ink_status";
 
-pub trait OpcodePricer: Fn(&Operator, &SigMap) -> u64 + Send + Sync + Clone {}
+pub trait OpcodePricer: Send + Sync + Clone {
+    fn price(&self, op: &Operator, sigs: &SigMap) -> u64;
+}
+
+impl<T> OpcodePricer for T
+where
+    T: Fn(&Operator, &SigMap) -> u64 + Send + Sync + Clone,
+{
+    fn price(&self, op: &Operator, sigs: &SigMap) -> u64 {
+        self(op, sigs)
+    }
+}
+
+/// Associates opcodes to their ink costs via data rather than code,
+/// allowing pricing schedules to be loaded at runtime.
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct CostTable {
+    /// Costs indexed by `OperatorCode::dense_index`
+    #[derivative(Debug = "ignore")]
+    costs: Arc<[Option<u64>]>,
+    /// The cost of any operator missing from the table
+    fallback: u64,
+}
+
+impl CostTable {
+    pub fn new<I>(costs: I, fallback: u64) -> Self
+    where
+        I: IntoIterator<Item = (OperatorCode, u64)>,
+    {
+        let mut table = vec![None; OperatorCode::DENSE_SLOTS];
+        for (op, cost) in costs {
+            table[op.dense_index()] = Some(cost);
+        }
+        let costs = table.into();
+        Self { costs, fallback }
+    }
+
+    /// Builds a table pricing just the given operators, with every other operator being free.
+    pub fn from_codes<I>(costs: I) -> Self
+    where
+        I: IntoIterator<Item = (OperatorCode, u64)>,
+    {
+        Self::new(costs, 0)
+    }
+
+    pub fn cost(&self, op: &Operator) -> u64 {
+        let index = OperatorCode::from(op).dense_index();
+        self.costs[index].unwrap_or(self.fallback)
+    }
+
+    /// Extracts the serializable form of the table, listing just the priced operators.
+    pub fn to_params(&self) -> CostTableParams {
+        let costs = self.costs.iter().enumerate();
+        let costs = costs.filter_map(|(index, cost)| cost.map(|cost| (index as u16, cost)));
+        CostTableParams {
+            costs: costs.collect(),
+            fallback: self.fallback,
+        }
+    }
+
+    /// Reconstructs a table from its serializable form, erring on out-of-range indices.
+    pub fn from_params(params: CostTableParams) -> Result<Self> {
+        let mut costs = Vec::with_capacity(params.costs.len());
+        for (index, cost) in params.costs {
+            let index = index as usize;
+            if index >= OperatorCode::DENSE_SLOTS {
+                bail!("cost table index {} is out of range", index.red());
+            }
+            costs.push((OperatorCode::from_dense_index(index), cost));
+        }
+        Ok(Self::new(costs, params.fallback))
+    }
+}
+
+/// The serializable form of a `CostTable`, with costs keyed by `OperatorCode::dense_index`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostTableParams {
+    /// The cost of each priced operator
+    pub costs: Vec<(u16, u64)>,
+    /// The cost of any operator missing from the table
+    pub fallback: u64,
+}
+
+impl OpcodePricer for CostTable {
+    fn price(&self, op: &Operator, _: &SigMap) -> u64 {
+        self.cost(op)
+    }
+}
+
+/// The pricer a `CompileConfig` meters with, which is either code or a `CostTable`.
+#[derive(Clone)]
+pub enum ConfigPricer {
+    Func(OpCosts),
+    Table(CostTable),
+}
 
-impl<T> OpcodePricer for T where T: Fn(&Operator, &SigMap) -> u64 + Send + Sync + Clone {}
+impl OpcodePricer for ConfigPricer {
+    fn price(&self, op: &Operator, sigs: &SigMap) -> u64 {
+        match self {
+            Self::Func(costs) => costs(op, sigs),
+            Self::Table(table) => table.cost(op),
+        }
+    }
+}
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -129,7 +236,7 @@ impl<'a, F: OpcodePricer> FuncMiddleware<'a> for FuncMeter<'a, F> {
 
         let end = op.ends_basic_block();
 
-        let op_cost = (self.costs)(&op, &self.sigs);
+        let op_cost = self.costs.price(&op, &self.sigs);
         let mut cost = self.block_cost.saturating_add(op_cost);
         self.block_cost = cost;
         self.block.push(op);
@@ -161,12 +268,18 @@ impl<'a, F: OpcodePricer> FuncMiddleware<'a> for FuncMeter<'a, F> {
 
             // include the cost of executing the header
             for op in &header {
-                cost = cost.saturating_add((self.costs)(op, &self.sigs))
+                cost = cost.saturating_add(self.costs.price(op, &self.sigs))
             }
             header[1] = I64Const { value: cost as i64 };
             header[9] = I64Const { value: cost as i64 };
 
-            out.extend(header);
+            // upon exhaustion, zero the ink left before trapping, just as `out_of_ink` does
+            // this only runs when trapping, so it's deliberately left out of the cost above
+            let clamp = [I64Const { value: 0 }, GlobalSet { global_index: ink }];
+
+            out.extend(header[..6].iter().cloned());
+            out.extend(clamp);
+            out.extend(header[6..].iter().cloned());
             out.extend(self.block.drain(..));
             self.block_cost = 0;
         }
@@ -178,6 +291,9 @@ impl<'a, F: OpcodePricer> FuncMiddleware<'a> for FuncMeter<'a, F> {
     }
 }
 
+/// The state of a program's meter.
+/// Once exhausted, the ink-left global always holds 0, whether the program trapped or a hostio
+/// ran out, so readers of the raw global never see a stale or partial balance.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MachineMeter {
     Ready(u64),
@@ -242,6 +358,14 @@ pub trait MeteredMachine {
         Err(OutOfInkError)
     }
 
+    /// The ink consumed since the meter was set to `start_ink`, which is all of it when exhausted.
+    fn ink_consumed(&mut self, start_ink: u64) -> u64 {
+        match self.ink_left() {
+            MachineMeter::Ready(ink_left) => start_ink.saturating_sub(ink_left),
+            MachineMeter::Exhausted => start_ink,
+        }
+    }
+
     fn ink_ready(&mut self) -> Result<u64, OutOfInkError> {
         let MachineMeter::Ready(ink_left) = self.ink_left() else {
             return self.out_of_ink();
@@ -291,6 +415,7 @@ pub trait MeteredMachine {
 pub trait GasMeteredMachine: MeteredMachine {
     fn pricing(&mut self) -> PricingParams;
 
+    /// The gas left, which errs once the meter is exhausted rather than reporting 0.
     fn gas_left(&mut self) -> Result<u64, OutOfInkError> {
         let pricing = self.pricing();
         match self.ink_left() {
@@ -310,6 +435,12 @@ pub trait GasMeteredMachine: MeteredMachine {
         self.require_ink(pricing.gas_to_ink(gas))
     }
 
+    /// Pays for copying bytes out of an EVM buffer, as `RETURNDATACOPY` would.
+    fn pay_for_evm_copy(&mut self, bytes: u64) -> Result<(), OutOfInkError> {
+        let words = evm::evm_words(bytes);
+        self.buy_gas(words.saturating_mul(evm::COPY_WORD_GAS))
+    }
+
     fn pay_for_evm_log(&mut self, topics: u32, data_len: u32) -> Result<(), OutOfInkError> {
         let cost = (1 + topics as u64) * evm::LOG_TOPIC_GAS;
         let cost = cost.saturating_add(data_len as u64 * evm::LOG_DATA_GAS);
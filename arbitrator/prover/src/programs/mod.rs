@@ -4,11 +4,11 @@
 use crate::{
     binary::{ExportKind, WasmBinary},
     memory::MemoryType,
-    value::{FunctionType as ArbFunctionType, Value},
+    value::{ArbValueType, FunctionType as ArbFunctionType, Value},
 };
-use arbutil::Color;
+use arbutil::{Color, DebugColor};
 use eyre::{bail, eyre, Report, Result};
-use fnv::FnvHashMap as HashMap;
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use std::fmt::Debug;
 use wasmer_types::{
     entity::EntityRef, FunctionIndex, GlobalIndex, GlobalInit, ImportIndex, LocalFunctionIndex,
@@ -30,6 +30,10 @@ pub mod config;
 pub mod counter;
 pub mod depth;
 pub mod dynamic;
+pub mod float;
+#[cfg(feature = "native")]
+pub mod fuel;
+pub mod grow;
 pub mod heap;
 pub mod memory;
 pub mod meter;
@@ -46,8 +50,41 @@ pub trait ModuleMod {
     fn all_functions(&self) -> Result<HashMap<FunctionIndex, ArbFunctionType>>;
     fn all_signatures(&self) -> Result<HashMap<SignatureIndex, ArbFunctionType>>;
     fn get_import(&self, module: &str, name: &str) -> Result<ImportIndex>;
-    fn move_start_function(&mut self, name: &str) -> Result<()>;
+    fn move_start_function(&mut self) -> Result<Option<String>>;
+    fn memory_count(&self) -> usize;
     fn memory_info(&self) -> Result<MemoryType>;
+
+    /// Drops every export not in `keep`, save for those Stylus requires.
+    fn retain_exports(&mut self, keep: &HashSet<String>);
+
+    /// Ensures the module exports the Stylus entry point with the expected signature.
+    fn require_entrypoint(&self) -> Result<()>;
+}
+
+/// Ensures the module has exactly one memory.
+fn check_memory_count(count: usize) -> Result<()> {
+    match count {
+        0 => bail!("missing memory export with name {}", "memory".red()),
+        1 => Ok(()),
+        _ => bail!(
+            "multi-memory extension not supported: found {} memories",
+            count.red()
+        ),
+    }
+}
+
+/// Checks that the entry point is of type λ(args_len) → status.
+fn check_entrypoint_ty(ty: ArbFunctionType) -> Result<()> {
+    if ty != ArbFunctionType::new(vec![ArbValueType::I32], vec![ArbValueType::I32]) {
+        bail!("wrong type for {}: {}", STYLUS_ENTRY_POINT.red(), ty.red());
+    }
+    Ok(())
+}
+
+/// Whether the export must survive `retain_exports`.
+/// This includes the entrypoint, memory, and any globals or functions added by instrumentation.
+fn required_export(name: &str) -> bool {
+    name == STYLUS_ENTRY_POINT || name == "memory" || name.starts_with("stylus")
 }
 
 pub trait Middleware<M: ModuleMod> {
@@ -222,31 +259,55 @@ impl ModuleMod for ModuleInfo {
             .ok_or_else(|| eyre!("missing import {}", name.red()))
     }
 
-    fn move_start_function(&mut self, name: &str) -> Result<()> {
-        if let Some(prior) = self.exports.get(name) {
-            bail!("function {} already exists @ index {:?}", name.red(), prior)
-        }
+    fn move_start_function(&mut self) -> Result<Option<String>> {
+        // only the start mover may name the start function
+        self.custom_sections.shift_remove(start::STYLUS_START);
+        let Some(start) = self.start_function.take() else {
+            return Ok(None);
+        };
+        let name = start::start_export_name(self.exports.keys().map(String::as_str))?;
+        let export = ExportIndex::Function(start);
+        self.exports.insert(name.clone(), export);
+        self.function_names.insert(start, name.clone());
+
+        // record the name in a custom section, which survives serialization
+        let data = name.clone().into_bytes().into_boxed_slice();
+        let section = self.custom_sections_data.push(data);
+        self.custom_sections
+            .insert(start::STYLUS_START.to_owned(), section);
+        Ok(Some(name))
+    }
 
-        if let Some(start) = self.start_function.take() {
-            let export = ExportIndex::Function(start);
-            self.exports.insert(name.to_owned(), export);
-            self.function_names.insert(start, name.to_owned());
-        }
-        Ok(())
+    fn memory_count(&self) -> usize {
+        self.memories.len()
     }
 
     fn memory_info(&self) -> Result<MemoryType> {
-        if self.memories.is_empty() {
-            bail!("missing memory export with name {}", "memory".red());
-        }
-        if self.memories.len() > 1 {
-            bail!("only one memory is allowed");
-        }
+        check_memory_count(self.memory_count())?;
         if self.exports.get("memory") != Some(&ExportIndex::Memory(MemoryIndex::from_u32(0))) {
             bail!("missing memory with export name {}", "memory".red());
         }
         Ok(self.memories.last().unwrap().into())
     }
+
+    fn retain_exports(&mut self, keep: &HashSet<String>) {
+        self.exports
+            .retain(|name, _| keep.contains(name) || required_export(name));
+    }
+
+    fn require_entrypoint(&self) -> Result<()> {
+        let Some(export) = self.exports.get(STYLUS_ENTRY_POINT) else {
+            bail!("missing export with name {}", STYLUS_ENTRY_POINT.red());
+        };
+        let ExportIndex::Function(func) = export else {
+            bail!(
+                "export {} must be a function but is a {}",
+                STYLUS_ENTRY_POINT.red(),
+                export.debug_red(),
+            );
+        };
+        check_entrypoint_ty(self.get_function(*func)?)
+    }
 }
 
 impl<'a> ModuleMod for WasmBinary<'a> {
@@ -334,39 +395,59 @@ impl<'a> ModuleMod for WasmBinary<'a> {
             .ok_or_else(|| eyre!("missing import {}", name.red()))
     }
 
-    fn move_start_function(&mut self, name: &str) -> Result<()> {
-        if let Some(prior) = self.exports.get(name) {
-            bail!("function {} already exists @ index {:?}", name.red(), prior)
-        }
+    fn move_start_function(&mut self) -> Result<Option<String>> {
+        let Some(start) = self.start.take() else {
+            return Ok(None);
+        };
+        let name = start::start_export_name(self.exports.keys().map(String::as_str))?;
+        self.exports.insert(name.clone(), (start, ExportKind::Func));
+        self.names.functions.insert(start, name.clone());
+        Ok(Some(name))
+    }
 
-        if let Some(start) = self.start.take() {
-            let name = name.to_owned();
-            self.exports.insert(name.clone(), (start, ExportKind::Func));
-            self.names.functions.insert(start, name);
-        }
-        Ok(())
+    fn memory_count(&self) -> usize {
+        self.memories.len()
     }
 
     fn memory_info(&self) -> Result<MemoryType> {
-        if self.memories.is_empty() {
-            bail!("missing memory export with name {}", "memory".red());
-        }
-        if self.memories.len() > 1 {
-            bail!("only one memory is allowed");
-        }
+        check_memory_count(self.memory_count())?;
         if self.exports.get("memory") != Some(&(0, ExportKind::Memory)) {
             bail!("missing memory with export name {}", "memory".red());
         }
         self.memories.last().unwrap().try_into()
     }
+
+    fn retain_exports(&mut self, keep: &HashSet<String>) {
+        self.exports
+            .retain(|name, _| keep.contains(name) || required_export(name));
+    }
+
+    fn require_entrypoint(&self) -> Result<()> {
+        let Some(&(func, kind)) = self.exports.get(STYLUS_ENTRY_POINT) else {
+            bail!("missing export with name {}", STYLUS_ENTRY_POINT.red());
+        };
+        if kind != ExportKind::Func {
+            bail!(
+                "export {} must be a function but is a {}",
+                STYLUS_ENTRY_POINT.red(),
+                kind.debug_red(),
+            );
+        }
+        check_entrypoint_ty(self.get_function(FunctionIndex::new(func.try_into()?))?)
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct StylusData {
     pub ink_left: GlobalIndex,
     pub ink_status: GlobalIndex,
     pub depth_left: GlobalIndex,
+    /// The number of pages the program's memory starts with
     pub footprint: u16,
+    /// The maximum number of pages the program may start with
+    pub heap_bound: u16,
+    /// The name the start function is exported under, if the program has one
+    pub start_export: Option<String>,
 }
 
 impl StylusData {
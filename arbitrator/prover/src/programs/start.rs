@@ -2,7 +2,8 @@
 // For license information, see https://github.com/nitro/blob/master/LICENSE
 
 use super::{DefaultFuncMiddleware, Middleware, ModuleMod};
-use eyre::Result;
+use eyre::{eyre, Result};
+use parking_lot::RwLock;
 use wasmer_types::LocalFunctionIndex;
 
 #[cfg(feature = "native")]
@@ -11,13 +12,23 @@ use wasmer::TypedFunction;
 pub const STYLUS_START: &str = "stylus_start";
 
 #[derive(Debug, Default)]
-pub struct StartMover {}
+pub struct StartMover {
+    /// The name the start function was exported under, if the module has one
+    export: RwLock<Option<String>>,
+}
+
+impl StartMover {
+    pub fn export(&self) -> Option<String> {
+        self.export.read().clone()
+    }
+}
 
 impl<M: ModuleMod> Middleware<M> for StartMover {
     type FM<'a> = DefaultFuncMiddleware;
 
     fn update_module(&self, module: &mut M) -> Result<()> {
-        module.move_start_function(STYLUS_START)
+        *self.export.write() = module.move_start_function()?;
+        Ok(())
     }
 
     fn instrument<'a>(&self, _: LocalFunctionIndex) -> Result<Self::FM<'a>> {
@@ -29,6 +40,32 @@ impl<M: ModuleMod> Middleware<M> for StartMover {
     }
 }
 
+/// Picks the name to export the start function under, avoiding the module's own exports.
+/// When the module already exports names of the form `stylus_start` or `stylus_start_N`,
+/// the chosen name carries the next suffix.
+pub fn start_export_name<'a, I>(exports: I) -> Result<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    match exports.into_iter().filter_map(start_suffix).max() {
+        None => Ok(STYLUS_START.to_owned()),
+        Some(max) => {
+            let suffix = max
+                .checked_add(1)
+                .ok_or_else(|| eyre!("no start name available"))?;
+            Ok(format!("{STYLUS_START}_{suffix}"))
+        }
+    }
+}
+
+/// Parses the suffix of names of the form `stylus_start` (0) or `stylus_start_N` (N).
+pub fn start_suffix(name: &str) -> Option<u32> {
+    match name.strip_prefix(STYLUS_START)? {
+        "" => Some(0),
+        rest => rest.strip_prefix('_')?.parse().ok(),
+    }
+}
+
 #[cfg(feature = "native")]
 pub trait StartlessMachine {
     fn get_start(&self) -> Result<TypedFunction<(), ()>>;
@@ -3,8 +3,26 @@
 
 #![cfg(test)]
 
-use crate::binary;
-use std::path::Path;
+use crate::{
+    binary, encode,
+    programs::{
+        config::{
+            CompileConfig, CompileConfigParams, CompileMemoryParams, CompilePricingParams,
+            CompilerBackend, PricingParams, SigMap, StylusConfig, StylusConfigBuilder,
+        },
+        meter::{CostTable, OpcodePricer, STYLUS_INK_LEFT, STYLUS_INK_STATUS},
+    },
+    value::Value,
+};
+use arbutil::{Bytes20, Bytes32};
+use eyre::Result;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+use wasmer_types::Pages;
+use wasmparser::{ExternalKind, Operator, Parser, Payload, Validator};
 
 fn as_wasm(wat: &str) -> Vec<u8> {
     let wasm = wasmer::wat2wasm(wat.as_bytes());
@@ -32,6 +50,27 @@ pub fn reject_reexports() {
     let _ = binary::parse(&wasm, Path::new("")).unwrap_err();
 }
 
+#[test]
+pub fn reject_mistyped_globals() {
+    let wasm = as_wasm(
+        r#"
+        (module
+            (global $ok i64 (i64.const 0))
+            (global $float f32 (f32.const 1.5))
+        )"#,
+    );
+    let bin = binary::parse(&wasm, Path::new("")).unwrap();
+    assert_eq!(bin.globals, vec![Value::I64(0), Value::F32(1.5)]);
+
+    let wasm = as_wasm(
+        r#"
+        (module
+            (global $should_reject i64 (i32.const 0))
+        )"#,
+    );
+    let _ = binary::parse(&wasm, Path::new("")).unwrap_err();
+}
+
 #[test]
 pub fn reject_ambiguous_imports() {
     let wasm = as_wasm(
@@ -52,3 +91,388 @@ pub fn reject_ambiguous_imports() {
     );
     let _ = binary::parse(&wasm, Path::new("")).unwrap_err();
 }
+
+#[test]
+pub fn value_hash_matches_eq() {
+    let hash = |value: Value| {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    };
+    let nan = Value::F32(f32::NAN);
+    assert_eq!(nan, nan);
+    assert_eq!(hash(nan), hash(nan));
+
+    let (zero, negative_zero) = (Value::F32(0.0), Value::F32(-0.0));
+    assert_eq!(hash(zero), hash(Value::F32(0.0)));
+    assert_ne!(zero, negative_zero);
+    assert_ne!(hash(zero), hash(negative_zero));
+
+    // same bits, different types
+    assert_ne!(Value::I32(0), zero);
+    assert_ne!(hash(Value::I32(0)), hash(zero));
+    assert_eq!(hash(Value::I64(7)), hash(Value::I64(7)));
+}
+
+#[test]
+pub fn value_from_low_bytes() {
+    let mut word = [0xff; 32];
+    word[24..].copy_from_slice(&0x0102030405060708_u64.to_be_bytes());
+    let word = Bytes32(word);
+    assert_eq!(Value::from_bytes32_low_u32(word), Value::I32(0x05060708));
+    assert_eq!(
+        Value::from_bytes32_low_u64(word),
+        Value::I64(0x0102030405060708)
+    );
+
+    // the high bytes are discarded
+    let max = Bytes32([0xff; 32]);
+    assert_eq!(Value::from_bytes32_low_u32(max), Value::I32(u32::MAX));
+    assert_eq!(Value::from_bytes32_low_u64(max), Value::I64(u64::MAX));
+
+    // values that fit round trip
+    for value in [Value::I32(0), Value::I32(u32::MAX), Value::I32(1 << 31)] {
+        let word = value.contents_for_proof();
+        assert_eq!(Value::from_bytes32_low_u32(word), value);
+    }
+    for value in [Value::I64(0), Value::I64(u64::MAX), Value::I64(1 << 63)] {
+        let word = value.contents_for_proof();
+        assert_eq!(Value::from_bytes32_low_u64(word), value);
+    }
+
+    let mut addr = [0; 20];
+    addr[11] = 0xaa;
+    addr[12..].copy_from_slice(&0x1122334455667788_u64.to_be_bytes());
+    let addr = Bytes20(addr);
+    assert_eq!(Value::from_bytes20_low_u32(addr), Value::I32(0x55667788));
+    assert_eq!(
+        Value::from_bytes20_low_u64(addr),
+        Value::I64(0x1122334455667788)
+    );
+}
+
+#[test]
+pub fn dump_instrumented() -> Result<()> {
+    let wasm = as_wasm(
+        r#"
+        (module
+            (import "vm_hooks" "read_args" (func $read_args (param i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "stylus")
+            (table 1 funcref)
+            (elem (i32.const 0) $entry)
+            (func $entry (export "user_entrypoint") (param i32) (result i32)
+                (local i64 i64 f64)
+                (block (br_table 0 0 (local.get 0)))
+                (memory.copy (i32.const 8) (i32.const 0) (i32.const 6))
+                (drop (i64.load offset=8 (i32.const 0)))
+                (i32.const -1))
+        )"#,
+    );
+    let compile = CompileConfig::version(1, false);
+    assert!(encode::instrumented_wasm(&wasm, &compile).is_err());
+
+    let compile = CompileConfig::version(1, true);
+    let dumped = encode::instrumented_wasm(&wasm, &compile)?;
+    Validator::new().validate_all(&dumped)?;
+
+    let mut globals = vec![];
+    for payload in Parser::new(0).parse_all(&dumped) {
+        if let Payload::ExportSection(mut exports) = payload? {
+            for _ in 0..exports.get_count() {
+                let export = exports.read()?;
+                if let ExternalKind::Global = export.kind {
+                    globals.push(export.field);
+                }
+            }
+        }
+    }
+    assert!(globals.contains(&STYLUS_INK_LEFT));
+    assert!(globals.contains(&STYLUS_INK_STATUS));
+    Ok(())
+}
+
+/// Instruments a function using each operator the validator's features permit,
+/// ensuring every pass, the depth checker in particular, handles it without panicking.
+#[test]
+pub fn instrument_every_opcode() -> Result<()> {
+    let mut bodies: Vec<String> = vec![];
+    let mut add = |template: &str, ops: &str| {
+        for op in ops.split_whitespace() {
+            bodies.push(template.replace("OP", op));
+        }
+    };
+
+    let i32_ops = "i32.eqz i32.clz i32.ctz i32.popcnt i32.extend8_s i32.extend16_s
+        i64.extend_i32_s i64.extend_i32_u f32.convert_i32_s f32.convert_i32_u
+        f64.convert_i32_s f64.convert_i32_u f32.reinterpret_i32
+        i32.load i64.load f32.load f64.load i32.load8_s i32.load8_u i32.load16_s i32.load16_u
+        i64.load8_s i64.load8_u i64.load16_s i64.load16_u i64.load32_s i64.load32_u";
+    let i64_ops = "i64.eqz i64.clz i64.ctz i64.popcnt i64.extend8_s i64.extend16_s i64.extend32_s
+        i32.wrap_i64 f32.convert_i64_s f32.convert_i64_u f64.convert_i64_s f64.convert_i64_u
+        f64.reinterpret_i64";
+    let f32_ops = "f32.abs f32.neg f32.ceil f32.floor f32.trunc f32.nearest f32.sqrt
+        i32.trunc_f32_s i32.trunc_f32_u i64.trunc_f32_s i64.trunc_f32_u f64.promote_f32
+        i32.reinterpret_f32 i32.trunc_sat_f32_s i32.trunc_sat_f32_u
+        i64.trunc_sat_f32_s i64.trunc_sat_f32_u";
+    let f64_ops = "f64.abs f64.neg f64.ceil f64.floor f64.trunc f64.nearest f64.sqrt
+        i32.trunc_f64_s i32.trunc_f64_u i64.trunc_f64_s i64.trunc_f64_u f32.demote_f64
+        i64.reinterpret_f64 i32.trunc_sat_f64_s i32.trunc_sat_f64_u
+        i64.trunc_sat_f64_s i64.trunc_sat_f64_u";
+    let int_binops = "eq ne lt_s lt_u gt_s gt_u le_s le_u ge_s ge_u add sub mul div_s div_u
+        rem_s rem_u and or xor shl shr_s shr_u rotl rotr";
+    let float_binops = "eq ne lt gt le ge add sub mul div min max copysign";
+
+    add("(drop (OP (i32.const 0)))", i32_ops);
+    add("(drop (OP (i64.const 0)))", i64_ops);
+    add("(drop (OP (f32.const 0)))", f32_ops);
+    add("(drop (OP (f64.const 0)))", f64_ops);
+    add("(drop (i32.OP (i32.const 0) (i32.const 0)))", int_binops);
+    add("(drop (i64.OP (i64.const 0) (i64.const 0)))", int_binops);
+    add("(drop (f32.OP (f32.const 0) (f32.const 0)))", float_binops);
+    add("(drop (f64.OP (f64.const 0) (f64.const 0)))", float_binops);
+    add(
+        "(i32.OP (i32.const 0) (i32.const 0))",
+        "store store8 store16",
+    );
+    add(
+        "(i64.OP (i32.const 0) (i64.const 0))",
+        "store store8 store16 store32",
+    );
+    add("(OP.store (i32.const 0) (OP.const 0))", "f32 f64");
+    add("(drop (OP.const 0))", "i32 i64 f32 f64");
+    add(
+        "(memory.OP (i32.const 0) (i32.const 0) (i32.const 0))",
+        "fill copy",
+    );
+    add("OP", "nop");
+
+    let misc = [
+        "(drop (memory.size))",
+        "(drop (memory.grow (i32.const 0)))",
+        "(drop (local.get $l))",
+        "(local.set $l (i32.const 0))",
+        "(drop (local.tee $l (i32.const 0)))",
+        "(drop (global.get $g))",
+        "(global.set $g (i32.const 0))",
+        "(drop (select (i32.const 0) (i32.const 0) (i32.const 0)))",
+        "(block)",
+        "(loop)",
+        "(if (i32.const 0) (then) (else))",
+        "(if (i32.const 0) (then unreachable))",
+        "(if (i32.const 0) (then (return (i32.const 0))))",
+        "(block (br 0))",
+        "(block (br_if 0 (i32.const 0)))",
+        "(block (br_table 0 0 (i32.const 0)))",
+        "(drop (block (result i32) (i32.const 0)))",
+        "(i32.const 0) (block (param i32) (drop))",
+        "(drop (call $f (i32.const 0)))",
+        "(drop (call_indirect (type $t) (i32.const 0) (i32.const 0)))",
+    ];
+    bodies.extend(misc.map(str::to_owned));
+
+    // permitted by the bulk-memory extension, but rejected during instrumentation
+    let unsupported = [
+        "(memory.init $d (i32.const 0) (i32.const 0) (i32.const 0))",
+        "(data.drop $d)",
+        "(table.init $e (i32.const 0) (i32.const 0) (i32.const 0))",
+        "(elem.drop $e)",
+        "(table.copy (i32.const 0) (i32.const 0) (i32.const 0))",
+    ];
+
+    let module = |body: &str| {
+        as_wasm(&format!(
+            r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (memory (export "memory") 1 1)
+                (table 1 funcref)
+                (global $g (mut i32) (i32.const 0))
+                (data $d "x")
+                (elem $e func $f)
+                (func $f (export "user_entrypoint") (param i32) (result i32) (local $l i32)
+                    {body}
+                    (i32.const 0)))
+            "#
+        ))
+    };
+    let compile = CompileConfig::version(1, true);
+
+    for body in &bodies {
+        let wasm = module(body);
+        let mut bin = binary::parse(&wasm, Path::new("user"))?;
+        if let Err(error) = bin.instrument(&compile) {
+            panic!("failed to instrument {body}: {error:?}");
+        }
+    }
+    for body in unsupported {
+        let wasm = module(body);
+        let mut bin = binary::parse(&wasm, Path::new("user"))?;
+        let error = bin.instrument(&compile).unwrap_err();
+        assert!(
+            format!("{error:?}").contains("not fully supported"),
+            "{body}"
+        );
+    }
+
+    // operators outside the feature set never reach instrumentation
+    let features = binary::wasm_features();
+    assert!(!features.simd && !features.threads && !features.reference_types);
+    let simd = module("(drop (i32x4.extract_lane 0 (v128.const i32x4 0 0 0 0)))");
+    assert!(binary::parse(&simd, Path::new("user")).is_err());
+    Ok(())
+}
+
+#[test]
+pub fn config_params_roundtrip() -> Result<()> {
+    let compile = CompileConfig::version(1, true);
+    let json = serde_json::to_string(&compile.to_params())?;
+    let params = serde_json::from_str(&json)?;
+    let loaded = CompileConfig::from_params(params)?;
+
+    assert_eq!(loaded.to_params(), compile.to_params());
+    assert_eq!(loaded.bounds.heap_bound, Pages(128));
+    assert_eq!(
+        loaded.pricing.memory_fill_ink,
+        compile.pricing.memory_fill_ink
+    );
+
+    let sigs = SigMap::default();
+    let cost = |config: &CompileConfig, op| (config.pricing.costs)(&op, &sigs);
+    assert_eq!(cost(&loaded, Operator::F32Add), u64::MAX);
+    assert_eq!(
+        cost(&loaded, Operator::I32Add),
+        cost(&compile, Operator::I32Add)
+    );
+
+    let mut unknown = compile.to_params();
+    unknown.version = u16::MAX;
+    assert!(CompileConfig::from_params(unknown).is_err());
+
+    let mut pricey = compile.to_params();
+    pricey.memory_grow_ink = CompilePricingParams::MAX_MEMORY_GROW_INK + 1;
+    assert!(CompileConfig::from_params(pricey).is_err());
+
+    let mut partial: serde_json::Value = serde_json::from_str(&json)?;
+    partial.as_object_mut().unwrap().remove("max_data_segments");
+    assert!(serde_json::from_value::<CompileConfigParams>(partial).is_err());
+
+    let mut table = compile.clone();
+    table.pricing.cost_table = Some(CostTable::new([(Operator::I32Add.into(), 7)], 3));
+    let json = serde_json::to_string(&table.to_params())?;
+    let loaded = CompileConfig::from_params(serde_json::from_str(&json)?)?;
+    assert_eq!(loaded.fingerprint(), table.fingerprint());
+    assert_ne!(loaded.fingerprint(), compile.fingerprint());
+
+    let pricer = loaded.pricing.pricer();
+    assert_eq!(pricer.price(&Operator::I32Add, &sigs), 7);
+    assert_eq!(pricer.price(&Operator::I32Sub, &sigs), 3);
+
+    let mut malformed = table.to_params();
+    let costs = &mut malformed.cost_table.as_mut().unwrap().costs;
+    costs.push((u16::MAX, 1));
+    assert!(CompileConfig::from_params(malformed).is_err());
+
+    let config = StylusConfig::new(1, 10_000, 10_000);
+    let json = serde_json::to_string(&config)?;
+    assert_eq!(serde_json::from_str::<StylusConfig>(&json)?, config);
+    Ok(())
+}
+
+#[test]
+pub fn config_builder() -> Result<()> {
+    let (compile, config) = StylusConfigBuilder::new()
+        .debug_funcs(true)
+        .version(1)
+        .max_depth(20_000)
+        .ink_price(10_000)
+        .count_ops(true)
+        .build()?;
+
+    let preset = CompileConfig::version(1, true);
+    assert_eq!(config, StylusConfig::new(1, 20_000, 10_000));
+    assert_eq!(compile.bounds.heap_bound, preset.bounds.heap_bound);
+    assert_eq!(compile.bounds.max_frame_size, preset.bounds.max_frame_size);
+    assert!(compile.debug.debug_funcs);
+    assert!(compile.debug.count_ops);
+
+    let shallow = StylusConfigBuilder::new()
+        .version(1)
+        .max_depth(1024)
+        .build();
+    assert!(shallow.is_err());
+
+    let free = StylusConfigBuilder::new().ink_price(0).build();
+    assert!(free.is_err());
+    Ok(())
+}
+
+#[test]
+pub fn compile_memory_params() {
+    assert!(CompileMemoryParams::new(Pages(128), 1024, 4096).is_ok());
+    assert!(CompileMemoryParams::new(Pages(128), 0, 4096).is_err());
+    assert!(CompileMemoryParams::new(Pages(128), 1024, 0).is_err());
+    assert!(CompileMemoryParams::new(Pages(65537), 1024, 4096).is_err());
+
+    let (compile, _) = StylusConfigBuilder::new().version(1).build().unwrap();
+    let frame = compile.bounds.max_frame_size;
+    let deep = StylusConfigBuilder::new().version(1).max_depth(frame);
+    let shallow = StylusConfigBuilder::new().version(1).max_depth(frame - 1);
+    assert!(deep.build().is_ok());
+    assert!(shallow.build().is_err());
+}
+
+#[test]
+pub fn gas_ink_conversions() {
+    let unit = PricingParams::new(1);
+    assert_eq!(unit.try_gas_to_ink(u64::MAX).unwrap(), u64::MAX);
+    assert_eq!(unit.try_ink_to_gas(u64::MAX).unwrap(), u64::MAX);
+
+    let pricey = PricingParams::new(u32::MAX);
+    assert!(pricey.try_gas_to_ink(u64::MAX).is_err());
+    assert!(pricey.try_gas_to_ink(1 << 33).is_err());
+    assert_eq!(
+        pricey.try_gas_to_ink(1 << 32).unwrap(),
+        u64::MAX - (1 << 32) + 1
+    );
+    assert_eq!(pricey.gas_to_ink(u64::MAX), u64::MAX);
+
+    // ink rounds down to the nearest gas
+    let pricing = PricingParams::new(10_000);
+    assert_eq!(pricing.try_ink_to_gas(9_999).unwrap(), 0);
+    assert_eq!(pricing.try_ink_to_gas(10_001).unwrap(), 1);
+
+    let free = PricingParams::new(0);
+    assert!(free.try_gas_to_ink(1).is_err());
+    assert!(free.try_ink_to_gas(1).is_err());
+}
+
+#[test]
+pub fn debug_only_compilation() {
+    for debug_chain in [false, true] {
+        let mut compile = CompileConfig::version(1, debug_chain);
+        compile.debug.skip_verifier = true;
+        compile.debug.skip_nan_canonicalization = true;
+        assert_eq!(compile.verify(), !debug_chain);
+        assert_eq!(compile.canonicalize_nans(), !debug_chain);
+    }
+}
+
+#[test]
+pub fn compiler_backend_serialization() -> Result<()> {
+    let mut compile = CompileConfig::version(1, true);
+    compile.debug.backend = CompilerBackend::Cranelift;
+
+    // the backend serializes the same way regardless of the build's features
+    let json = serde_json::to_string(&compile.to_params())?;
+    assert!(json.contains(r#""backend":"Cranelift""#));
+    let loaded = CompileConfig::from_params(serde_json::from_str(&json)?)?;
+    assert_eq!(loaded.debug.backend, CompilerBackend::Cranelift);
+
+    let expected = match cfg!(feature = "cranelift") {
+        true => CompilerBackend::Cranelift,
+        false => CompilerBackend::Singlepass,
+    };
+    assert_eq!(loaded.backend(), expected);
+    Ok(())
+}
@@ -2,7 +2,7 @@
 // For license information, see https://github.com/nitro/blob/master/LICENSE
 
 use crate::binary::FloatType;
-use arbutil::{Bytes32, Color};
+use arbutil::{Bytes20, Bytes32, Color};
 use digest::Digest;
 use eyre::{bail, ErrReport, Result};
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,7 @@ use sha3::Keccak256;
 use std::{
     convert::{TryFrom, TryInto},
     fmt::Display,
+    hash::{Hash, Hasher},
     ops::Add,
 };
 use wasmparser::{FuncType, Type};
@@ -55,6 +56,23 @@ impl TryFrom<Type> for ArbValueType {
     }
 }
 
+impl TryFrom<wasmer_types::Type> for ArbValueType {
+    type Error = eyre::Error;
+
+    fn try_from(ty: wasmer_types::Type) -> Result<ArbValueType> {
+        use wasmer_types::Type::*;
+        Ok(match ty {
+            I32 => Self::I32,
+            I64 => Self::I64,
+            F32 => Self::F32,
+            F64 => Self::F64,
+            FuncRef => Self::FuncRef,
+            ExternRef => Self::FuncRef,
+            V128 => bail!("128-bit types are not supported"),
+        })
+    }
+}
+
 impl From<ArbValueType> for Type {
     fn from(ty: ArbValueType) -> Self {
         use ArbValueType::*;
@@ -268,6 +286,28 @@ impl Value {
         h.finalize().into()
     }
 
+    /// Makes an `I32` from the least significant 4 bytes of a big-endian word, discarding the rest.
+    /// Suited to handles that are known to fit, this inverts `contents_for_proof` for `I32` values.
+    pub fn from_bytes32_low_u32(word: Bytes32) -> Value {
+        Value::I32(u32::from_be_bytes(low_bytes(&word.0)))
+    }
+
+    /// Makes an `I64` from the least significant 8 bytes of a big-endian word, discarding the rest.
+    /// Suited to handles that are known to fit, this inverts `contents_for_proof` for `I64` values.
+    pub fn from_bytes32_low_u64(word: Bytes32) -> Value {
+        Value::I64(u64::from_be_bytes(low_bytes(&word.0)))
+    }
+
+    /// Makes an `I32` from the last 4 bytes of an address, discarding the rest.
+    pub fn from_bytes20_low_u32(addr: Bytes20) -> Value {
+        Value::I32(u32::from_be_bytes(low_bytes(&addr.0)))
+    }
+
+    /// Makes an `I64` from the last 8 bytes of an address, discarding the rest.
+    pub fn from_bytes20_low_u64(addr: Bytes20) -> Value {
+        Value::I64(u64::from_be_bytes(low_bytes(&addr.0)))
+    }
+
     pub fn default_of_type(ty: ArbValueType) -> Value {
         match ty {
             ArbValueType::I32 => Value::I32(0),
@@ -281,6 +321,11 @@ impl Value {
     }
 }
 
+/// Takes the last `N` bytes of a slice at least that long.
+fn low_bytes<const N: usize>(data: &[u8]) -> [u8; N] {
+    data[data.len() - N..].try_into().unwrap()
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let lparem = "(".grey();
@@ -400,8 +445,38 @@ impl TryInto<u64> for Value {
     }
 }
 
+impl TryInto<f32> for Value {
+    type Error = ErrReport;
+
+    fn try_into(self) -> Result<f32> {
+        match self {
+            Value::F32(value) => Ok(value),
+            _ => bail!("value not an f32"),
+        }
+    }
+}
+
+impl TryInto<f64> for Value {
+    type Error = ErrReport;
+
+    fn try_into(self) -> Result<f64> {
+        match self {
+            Value::F64(value) => Ok(value),
+            _ => bail!("value not an f64"),
+        }
+    }
+}
+
 impl Eq for Value {}
 
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // must mirror `PartialEq`
+        (self.ty() as u8).hash(state);
+        self.contents_for_proof().hash(state);
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FunctionType {
     pub inputs: Vec<ArbValueType>,
@@ -445,6 +520,19 @@ impl TryFrom<FuncType> for FunctionType {
     }
 }
 
+impl TryFrom<&wasmer_types::FunctionType> for FunctionType {
+    type Error = eyre::Error;
+
+    fn try_from(func: &wasmer_types::FunctionType) -> Result<Self> {
+        let convert = |types: &[wasmer_types::Type]| -> Result<Vec<_>> {
+            types.iter().map(|ty| ArbValueType::try_from(*ty)).collect()
+        };
+        let inputs = convert(func.params())?;
+        let outputs = convert(func.results())?;
+        Ok(Self { inputs, outputs })
+    }
+}
+
 impl Display for FunctionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut signature = "λ(".to_string();
@@ -0,0 +1,104 @@
+// Copyright 2023, Offchain Labs, Inc.
+// For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+use arbutil::{crypto, Bytes32};
+use lazy_static::lazy_static;
+use lru::LruCache;
+use parking_lot::Mutex;
+use prover::programs::config::CompileConfig;
+use std::num::NonZeroUsize;
+use wasmer::{Engine, Module, Store};
+
+lazy_static! {
+    static ref MODULE_CACHE: Mutex<ModuleCache> = Mutex::new(ModuleCache::new());
+}
+
+/// Deserialized modules are reused across calls, keyed by the hash of their serialized form
+/// and the fingerprint of the config they were compiled under.
+pub struct ModuleCache {
+    lru: LruCache<CacheKey, CacheItem>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    module_hash: Bytes32,
+    config: Bytes32,
+}
+
+impl CacheKey {
+    fn new(module_hash: Bytes32, compile: &CompileConfig) -> Self {
+        Self {
+            module_hash,
+            config: compile.fingerprint(),
+        }
+    }
+}
+
+/// A module and the engine it was deserialized with, from which fresh stores may be made.
+struct CacheItem {
+    module: Module,
+    engine: Engine,
+}
+
+impl CacheItem {
+    fn data(&self) -> (Module, Store) {
+        (self.module.clone(), Store::new(self.engine.clone()))
+    }
+}
+
+impl ModuleCache {
+    /// The default number of modules retained.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    fn new() -> Self {
+        let capacity = NonZeroUsize::new(Self::DEFAULT_CAPACITY).unwrap();
+        Self {
+            lru: LruCache::new(capacity),
+        }
+    }
+
+    /// Computes the key under which a serialized module is cached.
+    pub fn hash(module: &[u8]) -> Bytes32 {
+        crypto::keccak(module).into()
+    }
+
+    /// Resizes the cache, evicting the least recently used modules as needed.
+    /// A capacity of 0 is treated as 1.
+    pub fn set_capacity(capacity: usize) {
+        let capacity = NonZeroUsize::new(capacity.max(1)).unwrap();
+        MODULE_CACHE.lock().lru.resize(capacity);
+    }
+
+    /// Returns the number of modules currently cached.
+    pub fn len() -> usize {
+        MODULE_CACHE.lock().lru.len()
+    }
+
+    /// Drops all cached modules.
+    pub fn clear() {
+        MODULE_CACHE.lock().lru.clear();
+    }
+
+    /// Retrieves a cached module along with a fresh store for instantiating it.
+    pub fn get(module_hash: Bytes32, compile: &CompileConfig) -> Option<(Module, Store)> {
+        let key = CacheKey::new(module_hash, compile);
+        MODULE_CACHE.lock().lru.get(&key).map(CacheItem::data)
+    }
+
+    /// Caches a freshly deserialized module, handing it back for immediate use.
+    pub fn insert(
+        module_hash: Bytes32,
+        module: Module,
+        store: Store,
+        compile: &CompileConfig,
+    ) -> (Module, Store) {
+        let key = CacheKey::new(module_hash, compile);
+        let engine = store.engine().clone();
+        let item = CacheItem {
+            module: module.clone(),
+            engine,
+        };
+        MODULE_CACHE.lock().lru.put(key, item);
+        (module, store)
+    }
+}
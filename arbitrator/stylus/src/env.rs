@@ -2,13 +2,16 @@
 // For license information, see https://github.com/nitro/blob/master/LICENSE
 
 use arbutil::{
-    evm::{api::EvmApi, EvmData},
-    pricing, Bytes20, Bytes32, Color,
+    evm::{self, api::EvmApi, EvmData},
+    pricing::{HostioCosts, HostioKind},
+    Bytes20, Bytes32, Color,
 };
 use derivative::Derivative;
 use eyre::{eyre, ErrReport};
 use prover::programs::{config::PricingParams, meter::OutOfInkError, prelude::*};
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fmt::{Debug, Display},
     io,
     mem::MaybeUninit,
@@ -45,6 +48,174 @@ pub struct WasmEnv<E: EvmApi> {
     pub compile: CompileConfig,
     /// The runtime config
     pub config: Option<StylusConfig>,
+    /// The ink charged upon entering each hostio
+    pub hostio_ink: HostioCosts,
+    /// The minimum level of console output to print
+    pub console_level: ConsoleLevel,
+    /// Where console output is sent, which prints to stdout by default
+    #[derivative(Debug = "ignore")]
+    pub console_sink: ConsoleSink,
+    /// Lets the embedder cooperatively cancel long-running programs
+    #[derivative(Debug = "ignore")]
+    pub fuel_checkpoint: Option<FuelCheckpoint>,
+    /// The number of external calls currently in flight
+    call_depth: u32,
+    /// The EVM words of memory touched by copies, for pricing memory expansion
+    pub evm_memory_words: u64,
+    /// On debug chains, the gas each hostio has bought, keyed by the hostio's name
+    pub hostio_gas: HashMap<&'static str, u64>,
+    /// Resolves the preimages the debug-only `read_preimage` hostio asks for, which panics if unset
+    #[derivative(Debug = "ignore")]
+    pub preimage_oracle: Option<PreimageOracle>,
+}
+
+/// Periodically asks the embedder whether a program may keep running.
+/// The callback is consulted upon entering a hostio once at least `interval` ink has been spent
+/// since it last was, and returning `false` interrupts the program.
+/// Modules compiled with `fuel_checks` also consult it upon entering each function and loop,
+/// which programs that never call a hostio otherwise wouldn't.
+pub struct FuelCheckpoint {
+    /// The ink to spend between consultations
+    pub interval: u64,
+    /// Decides whether the program may continue
+    pub callback: Box<dyn FnMut() -> bool + Send>,
+    /// The ink level at or below which the callback is next consulted
+    next: u64,
+}
+
+impl FuelCheckpoint {
+    pub fn new<F: FnMut() -> bool + Send + 'static>(interval: u64, callback: F) -> Self {
+        Self {
+            interval,
+            callback: Box::new(callback),
+            next: u64::MAX,
+        }
+    }
+
+    /// Restarts the interval, as when a program begins with the given ink.
+    pub fn reset(&mut self, ink: u64) {
+        self.next = ink.saturating_sub(self.interval);
+    }
+
+    /// Consults the callback if the interval has elapsed, returning whether the program may continue.
+    pub fn check(&mut self, ink: u64) -> bool {
+        if ink > self.next {
+            return true;
+        }
+        self.reset(ink);
+        (self.callback)()
+    }
+}
+
+/// Resolves a keccak hash to its preimage, if known.
+pub type PreimageOracle = Box<dyn Fn(Bytes32) -> Option<Vec<u8>> + Send>;
+
+/// Receives each console message a program emits that meets the minimum level.
+pub type ConsoleSink = Box<dyn FnMut(ConsoleMessage<'_>) + Send>;
+
+/// The severity of a console message, from least to most urgent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum ConsoleLevel {
+    #[default]
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl TryFrom<u32> for ConsoleLevel {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, ()> {
+        use ConsoleLevel::*;
+        Ok(match value {
+            0 => Trace,
+            1 => Debug,
+            2 => Info,
+            3 => Warn,
+            4 => Error,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl Display for ConsoleLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ConsoleLevel::*;
+        match self {
+            Trace => write!(f, "trace"),
+            Debug => write!(f, "debug"),
+            Info => write!(f, "info"),
+            Warn => write!(f, "warn"),
+            Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single message printed via the console hostios.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleMessage<'a> {
+    /// The severity of the message
+    pub level: ConsoleLevel,
+    /// The message's contents
+    pub body: ConsoleBody<'a>,
+    /// Whether the message came from a `tee`, which also returns the value to the program
+    pub tee: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleBody<'a> {
+    Text(Cow<'a, str>),
+    Bytes(Cow<'a, [u8]>),
+}
+
+impl<'a> ConsoleMessage<'a> {
+    pub fn text<T: Into<Cow<'a, str>>>(level: ConsoleLevel, text: T) -> Self {
+        let body = ConsoleBody::Text(text.into());
+        Self {
+            level,
+            body,
+            tee: false,
+        }
+    }
+
+    pub fn bytes<T: Into<Cow<'a, [u8]>>>(level: ConsoleLevel, bytes: T) -> Self {
+        let body = ConsoleBody::Bytes(bytes.into());
+        Self {
+            level,
+            body,
+            tee: false,
+        }
+    }
+
+    pub fn into_owned(self) -> ConsoleMessage<'static> {
+        let body = match self.body {
+            ConsoleBody::Text(text) => ConsoleBody::Text(Cow::Owned(text.into_owned())),
+            ConsoleBody::Bytes(bytes) => ConsoleBody::Bytes(Cow::Owned(bytes.into_owned())),
+        };
+        ConsoleMessage { body, ..self }
+    }
+
+    /// Prints the message to stdout.
+    pub fn print(&self) {
+        let level = self.level;
+        let prefix = match level {
+            ConsoleLevel::Info => "Stylus says:".to_owned(),
+            _ => format!("Stylus says [{level}]:"),
+        };
+        println!("{} {}", prefix.yellow(), self.body);
+    }
+}
+
+impl Display for ConsoleBody<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "{text}"),
+            Self::Bytes(bytes) => write!(f, "0x{}", hex::encode(bytes)),
+        }
+    }
 }
 
 impl<E: EvmApi> WasmEnv<E> {
@@ -63,30 +234,78 @@ impl<E: EvmApi> WasmEnv<E> {
             outs: vec![],
             memory: None,
             meter: None,
+            hostio_ink: HostioCosts::default(),
+            console_level: ConsoleLevel::default(),
+            console_sink: Box::new(|msg: ConsoleMessage| msg.print()),
+            fuel_checkpoint: None,
+            call_depth: 0,
+            evm_memory_words: 0,
+            hostio_gas: HashMap::new(),
+            preimage_oracle: None,
+        }
+    }
+
+    /// The number of external calls this program is currently waiting on.
+    /// A nonzero depth means any code now running was reached via one of the program's own calls.
+    pub fn reentrancy_depth(&self) -> u32 {
+        self.call_depth
+    }
+
+    /// Enters an external call, saving the state a nested program might otherwise clobber.
+    /// The state is restored when the returned scope is dropped.
+    pub fn call_scope(&mut self) -> CallScope<'_, E> {
+        self.call_depth += 1;
+        CallScope {
+            args: self.args.clone(),
+            outs: self.outs.clone(),
+            return_data_len: self.evm_data.return_data_len,
+            env: self,
         }
     }
 
     pub fn start<'a>(
         env: &'a mut WasmEnvMut<'_, E>,
-        ink: u64,
+        kind: HostioKind,
     ) -> Result<HostioInfo<'a, E>, Escape> {
         let mut info = Self::start_free(env);
-        info.buy_ink(pricing::HOSTIO_INK + ink)?;
+        info.kind = Some(kind);
+        let ink = info.hostio_ink.get(kind);
+        info.buy_ink(ink)?;
+        info.checkpoint()?;
         Ok(info)
     }
 
     pub fn start_free<'a>(env: &'a mut WasmEnvMut<'_, E>) -> HostioInfo<'a, E> {
         let (env, store) = env.data_and_store_mut();
         let memory = env.memory.clone().unwrap();
-        HostioInfo { env, memory, store }
+        HostioInfo {
+            env,
+            memory,
+            store,
+            kind: None,
+        }
     }
 
     pub fn meter(&mut self) -> &mut MeterData {
         self.meter.as_mut().expect("not metered")
     }
 
-    pub fn say<D: Display>(&self, text: D) {
-        println!("{} {text}", "Stylus says:".yellow());
+    pub fn say<D: Display>(&mut self, text: D) {
+        self.say_at(ConsoleLevel::Info, text);
+    }
+
+    /// Prints the message if it meets the minimum console level, returning whether it did.
+    pub fn say_at<D: Display>(&mut self, level: ConsoleLevel, text: D) -> bool {
+        self.emit(ConsoleMessage::text(level, text.to_string()))
+    }
+
+    /// Sends the message to the console sink if it meets the minimum level, returning whether it did.
+    pub fn emit(&mut self, message: ConsoleMessage) -> bool {
+        if message.level < self.console_level {
+            return false;
+        }
+        (self.console_sink)(message);
+        true
     }
 }
 
@@ -125,13 +344,38 @@ pub struct HostioInfo<'a, E: EvmApi> {
     pub env: &'a mut WasmEnv<E>,
     pub memory: Memory,
     pub store: StoreMut<'a>,
+    /// The hostio being executed, to which any gas bought is attributed
+    pub kind: Option<HostioKind>,
 }
 
 impl<'a, E: EvmApi> HostioInfo<'a, E> {
+    /// Consults the fuel checkpoint, if any, escaping when the embedder cancels the program.
+    pub fn checkpoint(&mut self) -> Result<(), Escape> {
+        let ink = self.env.meter().ink();
+        match &mut self.env.fuel_checkpoint {
+            Some(checkpoint) if !checkpoint.check(ink) => Err(Escape::Interrupted),
+            _ => Ok(()),
+        }
+    }
+
     pub fn config(&self) -> StylusConfig {
         self.config.expect("no config")
     }
 
+    /// Pays for an EVM-style copy of `len` bytes to `dest`, including any memory expansion.
+    pub fn pay_for_evm_copy_into(&mut self, dest: u32, len: u32) -> MaybeEscape {
+        self.pay_for_evm_copy(len.into())?;
+        self.pay_for_memory_expansion(dest, len)
+    }
+
+    /// Pays for growing the memory touched by EVM-style copies to cover `len` bytes at `dest`.
+    pub fn pay_for_memory_expansion(&mut self, dest: u32, len: u32) -> MaybeEscape {
+        let (words, cost) = evm::memory_expansion(self.evm_memory_words, dest, len);
+        self.buy_gas(cost)?;
+        self.evm_memory_words = words;
+        Ok(())
+    }
+
     pub fn pricing(&self) -> PricingParams {
         self.config().pricing
     }
@@ -220,6 +464,15 @@ impl<'a, E: EvmApi> GasMeteredMachine for HostioInfo<'a, E> {
     fn pricing(&mut self) -> PricingParams {
         self.config().pricing
     }
+
+    fn buy_gas(&mut self, gas: u64) -> Result<(), OutOfInkError> {
+        let pricing = self.pricing();
+        self.buy_ink(pricing.gas_to_ink(gas))?;
+        if let Some(kind) = self.kind.filter(|_| self.compile.debug.debug_funcs) {
+            *self.env.hostio_gas.entry(kind.name()).or_default() += gas;
+        }
+        Ok(())
+    }
 }
 
 impl<'a, E: EvmApi> Deref for HostioInfo<'a, E> {
@@ -236,6 +489,37 @@ impl<'a, E: EvmApi> DerefMut for HostioInfo<'a, E> {
     }
 }
 
+/// Guards a program's args, outs, and return data length across an external call.
+pub struct CallScope<'a, E: EvmApi> {
+    env: &'a mut WasmEnv<E>,
+    args: Vec<u8>,
+    outs: Vec<u8>,
+    return_data_len: u32,
+}
+
+impl<'a, E: EvmApi> Deref for CallScope<'a, E> {
+    type Target = WasmEnv<E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.env
+    }
+}
+
+impl<'a, E: EvmApi> DerefMut for CallScope<'a, E> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.env
+    }
+}
+
+impl<'a, E: EvmApi> Drop for CallScope<'a, E> {
+    fn drop(&mut self) {
+        self.env.args = std::mem::take(&mut self.args);
+        self.env.outs = std::mem::take(&mut self.outs);
+        self.env.evm_data.return_data_len = self.return_data_len;
+        self.env.call_depth -= 1;
+    }
+}
+
 pub type MaybeEscape = Result<(), Escape>;
 
 #[derive(Error, Debug)]
@@ -248,6 +532,14 @@ pub enum Escape {
     Logical(ErrReport),
     #[error("out of ink")]
     OutOfInk,
+    #[error("interrupted")]
+    Interrupted,
+    #[error("reverted")]
+    Revert,
+    #[error("out of memory")]
+    OutOfMemory,
+    #[error("exited")]
+    Exit,
 }
 
 impl Escape {
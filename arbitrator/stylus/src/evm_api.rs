@@ -65,7 +65,16 @@ pub struct GoEvmApi {
         unsafe extern "C" fn(id: usize, address: Bytes20, gas_cost: *mut u64) -> Bytes32, // balance
     pub account_codehash:
         unsafe extern "C" fn(id: usize, address: Bytes20, gas_cost: *mut u64) -> Bytes32, // codehash
+    pub account_nonce: unsafe extern "C" fn(id: usize, address: Bytes20, gas_cost: *mut u64) -> u64, // nonce
+    pub account_storage_root:
+        unsafe extern "C" fn(id: usize, address: Bytes20, gas_cost: *mut u64) -> Bytes32, // root
     pub add_pages: unsafe extern "C" fn(id: usize, pages: u16) -> u64, // gas cost
+    pub selfdestruct: unsafe extern "C" fn(
+        id: usize,
+        beneficiary: Bytes20,
+        gas_cost: *mut u64,
+        error: *mut RustVec,
+    ) -> EvmApiStatus,
     pub id: usize,
 }
 
@@ -241,13 +250,36 @@ impl EvmApi for GoEvmApi {
         (value, cost)
     }
 
+    fn account_nonce(&mut self, address: Bytes20) -> (u64, u64) {
+        let mut cost = 0;
+        let nonce = call!(self, account_nonce, address, ptr!(cost));
+        (nonce, cost)
+    }
+
     fn account_codehash(&mut self, address: Bytes20) -> (Bytes32, u64) {
         let mut cost = 0;
         let value = call!(self, account_codehash, address, ptr!(cost));
         (value, cost)
     }
 
+    fn account_storage_root(&mut self, address: Bytes20) -> (Bytes32, u64) {
+        let mut cost = 0;
+        let value = call!(self, account_storage_root, address, ptr!(cost));
+        (value, cost)
+    }
+
     fn add_pages(&mut self, pages: u16) -> u64 {
         call!(self, add_pages, pages)
     }
+
+    fn selfdestruct(&mut self, beneficiary: Bytes20) -> Result<u64> {
+        let mut error = RustVec::new(vec![]);
+        let mut cost = 0;
+        let api_status = call!(self, selfdestruct, beneficiary, ptr!(cost), ptr!(error));
+        let error = into_vec!(error); // done here to always drop
+        match api_status {
+            EvmApiStatus::Success => Ok(cost),
+            EvmApiStatus::Failure => Err(error!(error)),
+        }
+    }
 }
@@ -3,35 +3,67 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use crate::env::{Escape, MaybeEscape, WasmEnv, WasmEnvMut};
+use crate::env::{ConsoleLevel, ConsoleMessage, Escape, MaybeEscape, WasmEnv, WasmEnvMut};
 use arbutil::{
-    crypto,
+    blake2f, bn254, crypto,
     evm::{self, api::EvmApi, user::UserOutcomeKind},
-    pricing::{EVM_API_INK, HOSTIO_INK, PTR_INK},
+    pricing::{HostioKind, HOSTIO_INK},
     Bytes20, Bytes32,
 };
 use prover::{programs::prelude::*, value::Value};
 
 pub(crate) fn read_args<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 0)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::ReadArgs)?;
+    env.pay_for_memory_expansion(ptr, env.args.len() as u32)?;
     env.pay_for_write(env.args.len() as u64)?;
     env.write_slice(ptr, &env.args)?;
     Ok(())
 }
 
+pub(crate) fn read_args_slice<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    dest: u32,
+    offset: u32,
+    len: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::ReadArgsSlice)?;
+    let end = offset.checked_add(len);
+    if end.map_or(true, |end| end as usize > env.args.len()) {
+        return Escape::logical("args slice out of range");
+    }
+    env.pay_for_evm_copy_into(dest, len)?;
+
+    let (offset, len) = (offset as usize, len as usize);
+    env.write_slice(dest, &env.args[offset..offset + len])?;
+    Ok(())
+}
+
+pub(crate) fn args_len<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u32, Escape> {
+    let env = WasmEnv::start(&mut env, HostioKind::ArgsLen)?;
+    Ok(env.args.len() as u32)
+}
+
 pub(crate) fn write_result<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32, len: u32) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 0)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::WriteResult)?;
     env.pay_for_read(len.into())?;
     env.outs = env.read_slice(ptr, len)?;
     Ok(())
 }
 
+/// Stops the program, reverting with the given data as `revert(bytes)` would in Solidity.
+pub(crate) fn abort<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32, len: u32) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::Abort)?;
+    env.pay_for_read(len.into())?;
+    env.outs = env.read_slice(ptr, len)?;
+    Err(Escape::Revert)
+}
+
 pub(crate) fn storage_load_bytes32<E: EvmApi>(
     mut env: WasmEnvMut<E>,
     key: u32,
     dest: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 2 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::StorageLoadBytes32)?;
     let key = env.read_bytes32(key)?;
     let (value, gas_cost) = env.evm_api.get_bytes32(key);
     env.buy_gas(gas_cost)?;
@@ -44,7 +76,7 @@ pub(crate) fn storage_store_bytes32<E: EvmApi>(
     key: u32,
     value: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 2 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::StorageStoreBytes32)?;
     env.require_gas(evm::SSTORE_SENTRY_GAS)?; // see operations_acl_arbitrum.go
 
     let key = env.read_bytes32(key)?;
@@ -67,7 +99,10 @@ pub(crate) fn call_contract<E: EvmApi>(
     let call = |api: &mut E, contract, data, gas, value: Option<_>| {
         api.contract_call(contract, data, gas, value.unwrap())
     };
-    do_call(env, contract, data, data_len, value, gas, ret_len, call)
+    let kind = HostioKind::CallContract;
+    do_call(
+        env, contract, data, data_len, value, gas, ret_len, kind, call,
+    )
 }
 
 pub(crate) fn delegate_call_contract<E: EvmApi>(
@@ -79,7 +114,10 @@ pub(crate) fn delegate_call_contract<E: EvmApi>(
     ret_len: u32,
 ) -> Result<u8, Escape> {
     let call = |api: &mut E, contract, data, gas, _| api.delegate_call(contract, data, gas);
-    do_call(env, contract, data, data_len, None, gas, ret_len, call)
+    let kind = HostioKind::DelegateCallContract;
+    do_call(
+        env, contract, data, data_len, None, gas, ret_len, kind, call,
+    )
 }
 
 pub(crate) fn static_call_contract<E: EvmApi>(
@@ -91,7 +129,10 @@ pub(crate) fn static_call_contract<E: EvmApi>(
     ret_len: u32,
 ) -> Result<u8, Escape> {
     let call = |api: &mut E, contract, data, gas, _| api.static_call(contract, data, gas);
-    do_call(env, contract, data, data_len, None, gas, ret_len, call)
+    let kind = HostioKind::StaticCallContract;
+    do_call(
+        env, contract, data, data_len, None, gas, ret_len, kind, call,
+    )
 }
 
 pub(crate) fn do_call<F, E>(
@@ -102,22 +143,24 @@ pub(crate) fn do_call<F, E>(
     value: Option<u32>,
     mut gas: u64,
     return_data_len: u32,
+    kind: HostioKind,
     call: F,
 ) -> Result<u8, Escape>
 where
     E: EvmApi,
     F: FnOnce(&mut E, Bytes20, Vec<u8>, u64, Option<Bytes32>) -> (u32, u64, UserOutcomeKind),
 {
-    let mut env = WasmEnv::start(&mut env, 3 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, kind)?;
     env.pay_for_read(calldata_len.into())?;
     gas = gas.min(env.gas_left()?); // provide no more than what the user has
 
     let contract = env.read_bytes20(contract)?;
     let input = env.read_slice(calldata, calldata_len)?;
     let value = value.map(|x| env.read_bytes32(x)).transpose()?;
-    let api = &mut env.evm_api;
 
-    let (outs_len, gas_cost, status) = call(api, contract, input, gas, value);
+    let mut scope = env.call_scope();
+    let (outs_len, gas_cost, status) = call(&mut scope.evm_api, contract, input, gas, value);
+    drop(scope);
     env.buy_gas(gas_cost)?;
     env.evm_data.return_data_len = outs_len;
     env.write_u32(return_data_len, outs_len)?;
@@ -132,14 +175,14 @@ pub(crate) fn create1<E: EvmApi>(
     contract: u32,
     revert_data_len: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 3 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::Create1)?;
     env.pay_for_read(code_len.into())?;
 
     let code = env.read_slice(code, code_len)?;
     let endowment = env.read_bytes32(endowment)?;
     let gas = env.gas_left()?;
 
-    let (result, ret_len, gas_cost) = env.evm_api.create1(code, endowment, gas);
+    let (result, ret_len, gas_cost) = env.call_scope().evm_api.create1(code, endowment, gas);
     env.buy_gas(gas_cost)?;
     env.evm_data.return_data_len = ret_len;
     env.write_u32(revert_data_len, ret_len)?;
@@ -156,7 +199,7 @@ pub(crate) fn create2<E: EvmApi>(
     contract: u32,
     revert_data_len: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 4 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::Create2)?;
     env.pay_for_read(code_len.into())?;
 
     let code = env.read_slice(code, code_len)?;
@@ -164,7 +207,7 @@ pub(crate) fn create2<E: EvmApi>(
     let salt = env.read_bytes32(salt)?;
     let gas = env.gas_left()?;
 
-    let (result, ret_len, gas_cost) = env.evm_api.create2(code, endowment, salt, gas);
+    let (result, ret_len, gas_cost) = env.call_scope().evm_api.create2(code, endowment, salt, gas);
     env.buy_gas(gas_cost)?;
     env.evm_data.return_data_len = ret_len;
     env.write_u32(revert_data_len, ret_len)?;
@@ -172,13 +215,31 @@ pub(crate) fn create2<E: EvmApi>(
     Ok(())
 }
 
+/// Computes the address `create2` would deploy `code_hash` to for the given salt, without deploying.
+pub(crate) fn create2_address<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    code_hash: u32,
+    salt: u32,
+    dest: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::Create2Address)?;
+    env.pay_for_keccak(85)?;
+
+    let code_hash = env.read_bytes32(code_hash)?;
+    let salt = env.read_bytes32(salt)?;
+    let address = evm::create2_address(env.evm_data.contract_address, salt, code_hash);
+    env.write_bytes20(dest, address)?;
+    Ok(())
+}
+
 pub(crate) fn read_return_data<E: EvmApi>(
     mut env: WasmEnvMut<E>,
     dest: u32,
     offset: u32,
     size: u32,
 ) -> Result<u32, Escape> {
-    let mut env = WasmEnv::start(&mut env, EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::ReadReturnData)?;
+    env.pay_for_memory_expansion(dest, size)?;
     env.pay_for_write(size.into())?;
 
     let data = env.evm_api.get_return_data(offset, size);
@@ -187,8 +248,32 @@ pub(crate) fn read_return_data<E: EvmApi>(
     Ok(data.len() as u32)
 }
 
+/// Copies exactly `size` bytes of the return data, starting at `offset`.
+/// Unlike `read_return_data`, the window must lie within the buffer.
+pub(crate) fn read_return_data_slice<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    dest: u32,
+    offset: u32,
+    size: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::ReadReturnDataSlice)?;
+    let end = offset.checked_add(size);
+    if end.map_or(true, |end| end > env.evm_data.return_data_len) {
+        return Escape::logical("return data slice out of range");
+    }
+    env.pay_for_evm_copy_into(dest, size)?;
+    env.pay_for_write(size.into())?;
+
+    let data = env.evm_api.get_return_data(offset, size);
+    if data.len() != size as usize {
+        return Escape::logical("return data shorter than requested");
+    }
+    env.write_slice(dest, &data)?;
+    Ok(())
+}
+
 pub(crate) fn return_data_size<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u32, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::ReturnDataSize)?;
     let len = env.evm_data.return_data_len;
     Ok(len)
 }
@@ -199,7 +284,7 @@ pub(crate) fn emit_log<E: EvmApi>(
     len: u32,
     topics: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::EmitLog)?;
     if topics > 4 || len < topics * 32 {
         return Escape::logical("bad topic data");
     }
@@ -211,12 +296,34 @@ pub(crate) fn emit_log<E: EvmApi>(
     Ok(())
 }
 
+/// Emits a log whose topics and data reside in separate buffers.
+pub(crate) fn emit_log_separate<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    topics: u32,
+    count: u32,
+    data: u32,
+    len: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::EmitLogSeparate)?;
+    if count > 4 {
+        return Escape::logical("bad topic data");
+    }
+    env.pay_for_read((count * 32).into())?;
+    env.pay_for_read(len.into())?;
+    env.pay_for_evm_log(count, len)?;
+
+    let mut log = env.read_slice(topics, count * 32)?;
+    log.extend(env.read_slice(data, len)?);
+    env.evm_api.emit_log(log, count)?;
+    Ok(())
+}
+
 pub(crate) fn account_balance<E: EvmApi>(
     mut env: WasmEnvMut<E>,
     address: u32,
     ptr: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 2 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::AccountBalance)?;
     let address = env.read_bytes20(address)?;
     let (balance, gas_cost) = env.evm_api.account_balance(address);
     env.buy_gas(gas_cost)?;
@@ -229,7 +336,7 @@ pub(crate) fn account_codehash<E: EvmApi>(
     address: u32,
     ptr: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 2 * PTR_INK + EVM_API_INK)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::AccountCodehash)?;
     let address = env.read_bytes20(address)?;
     let (hash, gas_cost) = env.evm_api.account_codehash(address);
     env.buy_gas(gas_cost)?;
@@ -237,67 +344,115 @@ pub(crate) fn account_codehash<E: EvmApi>(
     Ok(())
 }
 
+/// Writes the root of the account's storage trie, which is only meaningful with a backing state trie.
+pub(crate) fn account_storage_root<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    address: u32,
+    ptr: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::AccountStorageRoot)?;
+    let address = env.read_bytes20(address)?;
+    let (root, gas_cost) = env.evm_api.account_storage_root(address);
+    env.buy_gas(gas_cost)?;
+    env.write_bytes32(ptr, root)?;
+    Ok(())
+}
+
+pub(crate) fn account_nonce<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    address: u32,
+) -> Result<u64, Escape> {
+    let mut env = WasmEnv::start(&mut env, HostioKind::AccountNonce)?;
+    let address = env.read_bytes20(address)?;
+    let (nonce, gas_cost) = env.evm_api.account_nonce(address);
+    env.buy_gas(gas_cost)?;
+    Ok(nonce)
+}
+
+/// Sends the program's balance to the beneficiary, leaving its code in place as with EIP-6780.
+/// Like the opcode, this stops the program, which succeeds without returning data.
+pub(crate) fn selfdestruct<E: EvmApi>(mut env: WasmEnvMut<E>, beneficiary: u32) -> MaybeEscape {
+    let mut env = WasmEnv::start(&mut env, HostioKind::SelfDestruct)?;
+    let beneficiary = env.read_bytes20(beneficiary)?;
+    let gas_cost = env.evm_api.selfdestruct(beneficiary)?;
+    env.buy_gas(gas_cost)?;
+    env.outs.clear();
+    Err(Escape::Exit)
+}
+
 pub(crate) fn evm_gas_left<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u64, Escape> {
-    let mut env = WasmEnv::start(&mut env, 0)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::EvmGasLeft)?;
     Ok(env.gas_left()?)
 }
 
 pub(crate) fn evm_ink_left<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u64, Escape> {
-    let mut env = WasmEnv::start(&mut env, 0)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::EvmInkLeft)?;
     Ok(env.ink_ready()?)
 }
 
 pub(crate) fn block_basefee<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::BlockBasefee)?;
     env.write_bytes32(ptr, env.evm_data.block_basefee)?;
     Ok(())
 }
 
+pub(crate) fn block_prevrandao<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
+    let env = WasmEnv::start(&mut env, HostioKind::BlockPrevrandao)?;
+    env.write_bytes32(ptr, env.evm_data.block_prevrandao)?;
+    Ok(())
+}
+
+pub(crate) fn block_blob_basefee<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
+    let env = WasmEnv::start(&mut env, HostioKind::BlockBlobBasefee)?;
+    env.write_bytes32(ptr, env.evm_data.block_blob_basefee)?;
+    Ok(())
+}
+
 pub(crate) fn chainid<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u64, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::Chainid)?;
     Ok(env.evm_data.chainid)
 }
 
 pub(crate) fn block_coinbase<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::BlockCoinbase)?;
     env.write_bytes20(ptr, env.evm_data.block_coinbase)?;
     Ok(())
 }
 
 pub(crate) fn block_gas_limit<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u64, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::BlockGasLimit)?;
     Ok(env.evm_data.block_gas_limit)
 }
 
 pub(crate) fn block_number<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u64, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::BlockNumber)?;
     Ok(env.evm_data.block_number)
 }
 
 pub(crate) fn block_timestamp<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u64, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::BlockTimestamp)?;
     Ok(env.evm_data.block_timestamp)
 }
 
 pub(crate) fn contract_address<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::ContractAddress)?;
     env.write_bytes20(ptr, env.evm_data.contract_address)?;
     Ok(())
 }
 
 pub(crate) fn msg_reentrant<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u32, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::MsgReentrant)?;
     Ok(env.evm_data.reentrant)
 }
 
 pub(crate) fn msg_sender<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::MsgSender)?;
     env.write_bytes20(ptr, env.evm_data.msg_sender)?;
     Ok(())
 }
 
 pub(crate) fn msg_value<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::MsgValue)?;
     env.write_bytes32(ptr, env.evm_data.msg_value)?;
     Ok(())
 }
@@ -308,7 +463,7 @@ pub(crate) fn native_keccak256<E: EvmApi>(
     len: u32,
     output: u32,
 ) -> MaybeEscape {
-    let mut env = WasmEnv::start(&mut env, 0)?;
+    let mut env = WasmEnv::start(&mut env, HostioKind::NativeKeccak256)?;
     env.pay_for_keccak(len.into())?;
 
     let preimage = env.read_slice(input, len)?;
@@ -317,29 +472,121 @@ pub(crate) fn native_keccak256<E: EvmApi>(
     Ok(())
 }
 
+/// Adds two alt_bn128 G1 points, as with the 0x06 precompile.
+/// Returns 0 on success, or 1 if either point is malformed.
+pub(crate) fn lib_bn254_add<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    input: u32,
+    output: u32,
+) -> Result<u8, Escape> {
+    let mut env = WasmEnv::start(&mut env, HostioKind::Bn254Add)?;
+    env.buy_gas(evm::BN254_ADD_GAS)?;
+
+    let input = env.read_slice(input, bn254::ADD_INPUT_LEN as u32)?;
+    let Some(point) = bn254::add(&input.try_into().unwrap()) else {
+        return Ok(1);
+    };
+    env.write_slice(output, &point)?;
+    Ok(0)
+}
+
+/// Multiplies an alt_bn128 G1 point by a scalar, as with the 0x07 precompile.
+/// Returns 0 on success, or 1 if the point is malformed.
+pub(crate) fn lib_bn254_mul<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    input: u32,
+    output: u32,
+) -> Result<u8, Escape> {
+    let mut env = WasmEnv::start(&mut env, HostioKind::Bn254Mul)?;
+    env.buy_gas(evm::BN254_MUL_GAS)?;
+
+    let input = env.read_slice(input, bn254::MUL_INPUT_LEN as u32)?;
+    let Some(point) = bn254::mul(&input.try_into().unwrap()) else {
+        return Ok(1);
+    };
+    env.write_slice(output, &point)?;
+    Ok(0)
+}
+
+/// Performs the alt_bn128 pairing check, as with the 0x08 precompile, writing the 32-byte result.
+/// Returns 0 on success, or 1 if the input isn't a whole number of pairs or a point is malformed.
+pub(crate) fn lib_bn254_pairing<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    input: u32,
+    len: u32,
+    output: u32,
+) -> Result<u8, Escape> {
+    let mut env = WasmEnv::start(&mut env, HostioKind::Bn254Pairing)?;
+    env.pay_for_read(len.into())?;
+    let pairs = u64::from(len) / bn254::PAIR_LEN as u64;
+    env.buy_gas(evm::BN254_PAIRING_BASE_GAS + pairs * evm::BN254_PAIRING_POINT_GAS)?;
+
+    let input = env.read_slice(input, len)?;
+    let Some(success) = bn254::pairing(&input) else {
+        return Ok(1);
+    };
+    let mut result = Bytes32::default();
+    result[31] = success.into();
+    env.write_bytes32(output, result)?;
+    Ok(0)
+}
+
+/// Runs the BLAKE2b F compression function, as with the 0x09 precompile, writing the 64-byte state.
+/// Returns 0 on success, or 1 if the input isn't 213 bytes or its final block flag isn't 0 or 1.
+pub(crate) fn lib_blake2f<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    input: u32,
+    len: u32,
+    output: u32,
+) -> Result<u8, Escape> {
+    let mut env = WasmEnv::start(&mut env, HostioKind::Blake2f)?;
+    if len as usize != blake2f::INPUT_LEN {
+        return Ok(1);
+    }
+    let input = env.read_slice(input, len)?.try_into().unwrap();
+    env.buy_gas(blake2f::rounds(&input).into())?;
+
+    let Some(state) = blake2f::compress(&input) else {
+        return Ok(1);
+    };
+    env.write_slice(output, &state)?;
+    Ok(0)
+}
+
 pub(crate) fn tx_gas_price<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::TxGasPrice)?;
     env.write_bytes32(ptr, env.evm_data.tx_gas_price)?;
     Ok(())
 }
 
 pub(crate) fn tx_ink_price<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u32, Escape> {
-    let env = WasmEnv::start(&mut env, 0)?;
+    let env = WasmEnv::start(&mut env, HostioKind::TxInkPrice)?;
     Ok(env.pricing().ink_price)
 }
 
 pub(crate) fn tx_origin<E: EvmApi>(mut env: WasmEnvMut<E>, ptr: u32) -> MaybeEscape {
-    let env = WasmEnv::start(&mut env, PTR_INK)?;
+    let env = WasmEnv::start(&mut env, HostioKind::TxOrigin)?;
     env.write_bytes20(ptr, env.evm_data.tx_origin)?;
     Ok(())
 }
 
+pub(crate) fn tx_type<E: EvmApi>(mut env: WasmEnvMut<E>) -> Result<u32, Escape> {
+    let env = WasmEnv::start(&mut env, HostioKind::TxType)?;
+    Ok(env.evm_data.tx_type.into())
+}
+
 pub(crate) fn memory_grow<E: EvmApi>(mut env: WasmEnvMut<E>, pages: u16) -> MaybeEscape {
     let mut env = WasmEnv::start_free(&mut env);
     if pages == 0 {
         env.buy_ink(HOSTIO_INK)?;
         return Ok(());
     }
+    if let Some(bound) = env.compile.bounds.grow_bound {
+        let open = env.view().size().0;
+        if open.saturating_add(pages.into()) > bound.0 {
+            return Err(Escape::OutOfMemory);
+        }
+    }
     let gas_cost = env.evm_api.add_pages(pages);
     env.buy_gas(gas_cost)?;
     Ok(())
@@ -350,17 +597,43 @@ pub(crate) fn console_log_text<E: EvmApi>(
     ptr: u32,
     len: u32,
 ) -> MaybeEscape {
-    let env = WasmEnv::start_free(&mut env);
+    let mut env = WasmEnv::start_free(&mut env);
     let text = env.read_slice(ptr, len)?;
     env.say(String::from_utf8_lossy(&text));
     Ok(())
 }
 
+pub(crate) fn console_log_leveled<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    level: u32,
+    ptr: u32,
+    len: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start_free(&mut env);
+    let Ok(level) = ConsoleLevel::try_from(level) else {
+        return Escape::logical("invalid console level");
+    };
+    let text = env.read_slice(ptr, len)?;
+    env.say_at(level, String::from_utf8_lossy(&text));
+    Ok(())
+}
+
+pub(crate) fn console_log_bytes<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    ptr: u32,
+    len: u32,
+) -> MaybeEscape {
+    let mut env = WasmEnv::start_free(&mut env);
+    let bytes = env.read_slice(ptr, len)?;
+    env.emit(ConsoleMessage::bytes(ConsoleLevel::Info, bytes));
+    Ok(())
+}
+
 pub(crate) fn console_log<E: EvmApi, T: Into<Value>>(
     mut env: WasmEnvMut<E>,
     value: T,
 ) -> MaybeEscape {
-    let env = WasmEnv::start_free(&mut env);
+    let mut env = WasmEnv::start_free(&mut env);
     env.say(value.into());
     Ok(())
 }
@@ -369,9 +642,42 @@ pub(crate) fn console_tee<E: EvmApi, T: Into<Value> + Copy>(
     mut env: WasmEnvMut<E>,
     value: T,
 ) -> Result<T, Escape> {
-    let env = WasmEnv::start_free(&mut env);
-    env.say(value.into());
+    let mut env = WasmEnv::start_free(&mut env);
+    let mut message = ConsoleMessage::text(ConsoleLevel::Info, value.into().to_string());
+    message.tee = true;
+    env.emit(message);
     Ok(value)
 }
 
 pub(crate) fn null_host<E: EvmApi>(_: WasmEnvMut<E>) {}
+
+/// Called upon entering each function and loop of modules compiled with `fuel_checks`.
+pub(crate) fn fuel_checkpoint<E: EvmApi>(mut env: WasmEnvMut<E>) -> MaybeEscape {
+    let mut env = WasmEnv::start_free(&mut env);
+    env.checkpoint()
+}
+
+/// Copies up to `size` bytes of the preimage of the keccak hash at `hash_ptr`, starting at `offset`.
+/// Returns the number of bytes written. Panics if the embedder hasn't installed an oracle.
+pub(crate) fn read_preimage<E: EvmApi>(
+    mut env: WasmEnvMut<E>,
+    hash_ptr: u32,
+    dest: u32,
+    offset: u32,
+    size: u32,
+) -> Result<u32, Escape> {
+    let mut env = WasmEnv::start(&mut env, HostioKind::ReadPreimage)?;
+    env.pay_for_evm_copy_into(dest, size)?;
+
+    let hash = env.read_bytes32(hash_ptr)?;
+    let Some(oracle) = &env.preimage_oracle else {
+        panic!("user program tried to read preimage")
+    };
+    let Some(preimage) = oracle(hash) else {
+        return Escape::logical("unknown preimage");
+    };
+    let start = preimage.len().min(offset as usize);
+    let end = preimage.len().min(start.saturating_add(size as usize));
+    env.write_slice(dest, &preimage[start..end])?;
+    Ok((end - start) as u32)
+}
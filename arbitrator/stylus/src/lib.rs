@@ -1,22 +1,27 @@
 // Copyright 2022-2023, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use crate::evm_api::GoEvmApi;
+use crate::{cache::ModuleCache, evm_api::GoEvmApi};
 use arbutil::{
     evm::{
         user::{UserOutcome, UserOutcomeKind},
         EvmData,
     },
     format::DebugBytes,
+    operator::OperatorCode,
 };
 use eyre::ErrReport;
 use native::NativeInstance;
 use prover::{programs::prelude::*, Machine};
 use run::RunProgram;
-use std::mem;
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+};
 
 pub use prover;
 
+pub mod cache;
 pub mod env;
 mod evm_api;
 pub mod host;
@@ -82,6 +87,21 @@ impl RustVec {
     }
 }
 
+/// Marks a debug chain in the `debug_mode` flags of `stylus_compile` and `stylus_call`.
+pub const DEBUG_CHAIN: u32 = 1;
+
+/// Requests operator counts in the `debug_mode` flags of `stylus_compile` and `stylus_call`.
+/// Only honored on debug chains, and must be passed to both so the module's config matches.
+pub const DEBUG_COUNT_OPS: u32 = 2;
+
+/// The config a module is compiled and run with, given the `debug_mode` flags.
+pub fn debug_config(version: u16, debug_mode: u32) -> CompileConfig {
+    let debug_chain = debug_mode & DEBUG_CHAIN != 0;
+    let mut compile = CompileConfig::version(version, debug_chain);
+    compile.debug.count_ops = debug_chain && debug_mode & DEBUG_COUNT_OPS != 0;
+    compile
+}
+
 /// Ensures a user program can be proven.
 /// On success, `wasm_info` is populated with pricing information.
 /// On error, a message is written to `output`.
@@ -119,12 +139,12 @@ pub unsafe extern "C" fn stylus_parse_wasm(
 pub unsafe extern "C" fn stylus_compile(
     wasm: GoSliceData,
     version: u16,
-    debug_mode: bool,
+    debug_mode: u32,
     output: *mut RustVec,
 ) -> UserOutcomeKind {
     let wasm = wasm.slice();
     let output = &mut *output;
-    let compile = CompileConfig::version(version, debug_mode);
+    let compile = debug_config(version, debug_mode);
 
     let module = match native::module(wasm, compile) {
         Ok(module) => module,
@@ -135,11 +155,13 @@ pub unsafe extern "C" fn stylus_compile(
 }
 
 /// Calls a compiled user program.
+/// On debug chains, the gas each hostio bought is written to `hostio_gas`, and when
+/// `DEBUG_COUNT_OPS` is requested, the operator counts of a successful call to `op_counts`.
 ///
 /// # Safety
 ///
-/// `module` must represent a valid module produced from `stylus_compile`.
-/// `output` and `gas` must not be null.
+/// `module` must represent a valid module produced from `stylus_compile` with the same `debug_mode`.
+/// `output`, `op_counts`, `hostio_gas`, and `gas` must not be null.
 #[no_mangle]
 pub unsafe extern "C" fn stylus_call(
     module: GoSliceData,
@@ -147,16 +169,32 @@ pub unsafe extern "C" fn stylus_call(
     config: StylusConfig,
     go_api: GoEvmApi,
     evm_data: EvmData,
-    debug_chain: u32,
+    debug_mode: u32,
     output: *mut RustVec,
+    op_counts: *mut RustVec,
+    hostio_gas: *mut RustVec,
     gas: *mut u64,
 ) -> UserOutcomeKind {
     let module = module.slice();
     let calldata = calldata.slice().to_vec();
-    let compile = CompileConfig::version(config.version, debug_chain != 0);
+    let compile = debug_config(config.version, debug_mode);
+    let debug_chain = compile.debug.debug_funcs;
+    let count_ops = compile.debug.count_ops;
     let pricing = config.pricing;
     let output = &mut *output;
-    let ink = pricing.gas_to_ink(*gas);
+    let op_counts = &mut *op_counts;
+    let hostio_gas = &mut *hostio_gas;
+
+    let ink = match pricing.try_gas_to_ink(*gas) {
+        Ok(ink) => ink,
+        Err(error) => {
+            *gas = 0; // take all gas when the conversion overflows
+            return output.write_err(error.wrap_err("call failed"));
+        }
+    };
+    if let Err(error) = evm_data.validate() {
+        return output.write_err(error.wrap_err("call failed"));
+    }
 
     // Safety: module came from compile_user_wasm and we've paid for memory expansion
     let instance = unsafe { NativeInstance::deserialize(module, compile, go_api, evm_data) };
@@ -169,14 +207,60 @@ pub unsafe extern "C" fn stylus_call(
         Err(e) | Ok(UserOutcome::Failure(e)) => output.write_err(e.wrap_err("call failed")),
         Ok(outcome) => output.write_outcome(outcome),
     };
-    let ink_left = match status {
-        UserOutcomeKind::OutOfStack => 0, // take all gas when out of stack
-        _ => instance.ink_left().into(),
-    };
-    *gas = pricing.ink_to_gas(ink_left);
+    *gas = pricing.ink_to_gas(final_ink(status, &mut instance));
+
+    if count_ops && status == UserOutcomeKind::Success {
+        if let Ok(counts) = instance.operator_counts() {
+            op_counts.write(encode_op_counts(&counts));
+        }
+    }
+    if debug_chain {
+        hostio_gas.write(encode_hostio_gas(&instance.env().hostio_gas));
+    }
     status
 }
 
+/// Serializes operator counts as a sequence of big-endian (u16 opcode, u64 count) pairs.
+pub fn encode_op_counts(counts: &BTreeMap<OperatorCode, u64>) -> Vec<u8> {
+    let mut data = Vec::with_capacity(counts.len() * 10);
+    for (&op, count) in counts {
+        let code: usize = op.into();
+        data.extend((code as u16).to_be_bytes());
+        data.extend(count.to_be_bytes());
+    }
+    data
+}
+
+/// Renders per-hostio gas as comma-separated `name:gas` pairs, most expensive first.
+pub fn encode_hostio_gas(gas: &HashMap<&'static str, u64>) -> Vec<u8> {
+    let mut entries: Vec<_> = gas.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let entries: Vec<_> = entries
+        .into_iter()
+        .map(|(name, gas)| format!("{name}:{gas}"))
+        .collect();
+    entries.join(",").into_bytes()
+}
+
+/// The ink a call returns to its caller, which depends on how the program exited.
+///
+/// Programs that finish normally, revert, fail, are interrupted, or exceed their grow bound
+/// return what they didn't use.
+/// Running out of ink or stack consumes everything, even if ink remains in the latter case.
+pub fn final_ink<M: MeteredMachine>(status: UserOutcomeKind, machine: &mut M) -> u64 {
+    use UserOutcomeKind::*;
+    match status {
+        Success | Revert | Failure | Interrupted | OutOfMemory => machine.ink_left().into(),
+        OutOfInk | OutOfStack => 0,
+    }
+}
+
+/// Sets the number of deserialized modules retained across calls.
+#[no_mangle]
+pub extern "C" fn stylus_cache_resize(capacity: usize) {
+    ModuleCache::set_capacity(capacity)
+}
+
 /// Frees the vector. Does nothing when the vector is null.
 ///
 /// # Safety
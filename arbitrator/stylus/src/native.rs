@@ -2,22 +2,27 @@
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
 use crate::{
+    cache::ModuleCache,
     env::{MeterData, WasmEnv},
     host,
+    run::RunProgram,
 };
 use arbutil::{
-    evm::{api::EvmApi, EvmData},
+    evm::{api::EvmApi, user::UserOutcome, EvmData},
     operator::OperatorCode,
-    Color,
+    Bytes32, Color,
 };
 use eyre::{bail, eyre, ErrReport, Result};
-use prover::programs::{
-    config::PricingParams,
-    counter::{Counter, CountingMachine, OP_OFFSETS},
-    depth::STYLUS_STACK_LEFT,
-    meter::{STYLUS_INK_LEFT, STYLUS_INK_STATUS},
-    prelude::*,
-    start::STYLUS_START,
+use prover::{
+    programs::{
+        config::PricingParams,
+        counter::{Counter, CountingMachine},
+        depth::STYLUS_STACK_LEFT,
+        meter::{STYLUS_INK_LEFT, STYLUS_INK_STATUS},
+        prelude::*,
+        start,
+    },
+    value::FunctionType,
 };
 use std::{
     collections::BTreeMap,
@@ -25,23 +30,56 @@ use std::{
     ops::{Deref, DerefMut},
 };
 use wasmer::{
-    imports, AsStoreMut, Function, FunctionEnv, Global, Instance, Memory, Module, Pages, Store,
-    TypedFunction, Value, WasmTypeList,
+    imports, AsStoreMut, Extern, Function, FunctionEnv, Global, Imports, Instance, Memory, Module,
+    Pages, Store, TypedFunction, Value, WasmTypeList,
 };
 
+/// A program instantiated with its own store.
+/// Instances may be moved between threads but must never be used by more than one at a time.
 #[derive(Debug)]
 pub struct NativeInstance<E: EvmApi> {
     pub instance: Instance,
     pub store: Store,
     pub env: FunctionEnv<WasmEnv<E>>,
+    /// Memory image restored when the instance is reset for reuse
+    pub snapshot: Option<Vec<u8>>,
 }
 
+/// The metered state of an instance, captured so that speculative work can be rolled back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasSnapshot {
+    meter: MachineMeter,
+}
+
+impl GasSnapshot {
+    pub fn meter(&self) -> MachineMeter {
+        self.meter
+    }
+}
+
+/// The resources a call consumed, as gathered by `run_main_instrumented`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunStats {
+    /// The ink spent, which is everything supplied when the program ran out
+    pub ink_used: u64,
+    /// The stack words still held when the program stopped, which is nonzero only on traps
+    pub stack_used: u32,
+    /// How many times each operator executed, when the module counts them
+    pub op_counts: Option<BTreeMap<OperatorCode, u64>>,
+}
+
+/// The store exclusively owns the instance's state, including the globals `MeterData` points into,
+/// and the `EvmApi` is itself `Send`. Nothing is shared with other instances, so moving one is sound.
+/// Since `NativeInstance` is not `Sync`, concurrent use is ruled out by the borrow checker.
+unsafe impl<E: EvmApi> Send for NativeInstance<E> {}
+
 impl<E: EvmApi> NativeInstance<E> {
     pub fn new(instance: Instance, store: Store, env: FunctionEnv<WasmEnv<E>>) -> Self {
         let mut native = Self {
             instance,
             store,
             env,
+            snapshot: None,
         };
         if let Some(config) = native.env().config {
             native.set_stack(config.max_depth);
@@ -69,6 +107,97 @@ impl<E: EvmApi> NativeInstance<E> {
         self.memory().ty(&self.store).minimum
     }
 
+    /// The current size of linear memory in bytes.
+    pub fn memory_bytes(&self) -> usize {
+        self.memory().view(&self.store).data_size() as usize
+    }
+
+    /// Records the current contents of memory so that the instance may later be `reset`.
+    /// This should be done before the first call, when memory holds only the data segments.
+    pub fn snapshot(&mut self) -> Result<()> {
+        let memory = self.memory();
+        let memory = memory.view(&self.store);
+        let mut data = vec![0; memory.data_size() as usize];
+        memory.read(0, &mut data)?;
+        self.snapshot = Some(data);
+        Ok(())
+    }
+
+    /// Readies a snapshotted instance for another call, allowing it to be pooled.
+    ///
+    /// Restores memory to the snapshot, zeroing any pages grown since, and readies the meter
+    /// and stack for the new config. The args, outs, and EVM context are all replaced.
+    ///
+    /// Memory is never shrunk, and the program's own non-exported globals are left as is.
+    /// These are only consistent across calls when the previous call completed normally.
+    pub fn reset(&mut self, config: StylusConfig, evm_api: E, evm_data: EvmData) -> Result<()> {
+        let Some(snapshot) = self.snapshot.take() else {
+            bail!("instance has no snapshot to reset to")
+        };
+        let memory = self.memory();
+        let memory = memory.view(&self.store);
+        let size = memory.data_size() as usize;
+        memory.write(0, &snapshot)?;
+        memory.write(snapshot.len() as u64, &vec![0; size - snapshot.len()])?;
+        self.snapshot = Some(snapshot);
+
+        let env = self.env_mut();
+        env.args.clear();
+        env.outs.clear();
+        env.hostio_gas.clear();
+        env.config = Some(config);
+        env.evm_api = evm_api;
+        env.evm_data = evm_data;
+
+        self.set_ink(0);
+        self.set_stack(config.max_depth);
+        Ok(())
+    }
+
+    /// Calls the program's entrypoint, reading back the resources it used in a single pass.
+    pub fn run_main_instrumented(
+        &mut self,
+        args: &[u8],
+        config: StylusConfig,
+        ink: u64,
+    ) -> Result<(UserOutcome, RunStats)> {
+        let outcome = self.run_main(args, config, ink)?;
+        let op_counts = match self.env().compile.debug.count_ops {
+            true => Some(self.operator_counts()?),
+            false => None,
+        };
+        let stats = RunStats {
+            ink_used: ink.saturating_sub(self.ink_left().ink()),
+            stack_used: config.max_depth.saturating_sub(self.stack_left()),
+            op_counts,
+        };
+        Ok((outcome, stats))
+    }
+
+    /// Captures the ink left and the exhaustion status together.
+    pub fn gas_snapshot(&mut self) -> GasSnapshot {
+        let meter = self.ink_left();
+        GasSnapshot { meter }
+    }
+
+    /// Rolls the meter back to a prior snapshot, such as after a speculative call.
+    pub fn restore_gas(&mut self, snapshot: GasSnapshot) {
+        self.set_meter(snapshot.meter);
+    }
+
+    /// The program's exported functions and their signatures, sorted by name.
+    pub fn exported_functions(&self) -> Result<Vec<(String, FunctionType)>> {
+        let mut funcs = vec![];
+        for (name, export) in self.instance.exports.iter() {
+            if let Extern::Function(func) = export {
+                let ty = FunctionType::try_from(&func.ty(&self.store))?;
+                funcs.push((name.clone(), ty));
+            }
+        }
+        funcs.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(funcs)
+    }
+
     pub fn read_slice(&self, mem: &str, ptr: usize, len: usize) -> Result<Vec<u8>> {
         let memory = self.exports.get_memory(mem)?;
         let memory = memory.view(&self.store);
@@ -78,6 +207,8 @@ impl<E: EvmApi> NativeInstance<E> {
     }
 
     /// Creates a `NativeInstance` from a serialized module.
+    /// Deserialized modules are cached, so repeat calls skip straight to instantiation.
+    /// Errs if the module was compiled under a different `CompileConfig`.
     ///
     /// # Safety
     ///
@@ -88,10 +219,17 @@ impl<E: EvmApi> NativeInstance<E> {
         evm: E,
         evm_data: EvmData,
     ) -> Result<Self> {
+        let module = check_header(module, &compile)?;
         let env = WasmEnv::new(compile, None, evm, evm_data);
+        let module_hash = ModuleCache::hash(module);
+
+        if let Some((module, store)) = ModuleCache::get(module_hash, &env.compile) {
+            return Self::from_module(module, store, env, no_extra_imports);
+        }
         let store = env.compile.store();
         let module = Module::deserialize(&store, module)?;
-        Self::from_module(module, store, env)
+        let (module, store) = ModuleCache::insert(module_hash, module, store, &env.compile);
+        Self::from_module(module, store, env, no_extra_imports)
     }
 
     pub fn from_path(
@@ -101,14 +239,54 @@ impl<E: EvmApi> NativeInstance<E> {
         compile: &CompileConfig,
         config: StylusConfig,
     ) -> Result<Self> {
+        let wat_or_wasm = std::fs::read(path)?;
+        Self::from_wasm(&wat_or_wasm, evm_api, evm_data, compile, config)
+    }
+
+    pub fn from_wat(
+        source: &str,
+        evm_api: E,
+        evm_data: EvmData,
+        compile: &CompileConfig,
+        config: StylusConfig,
+    ) -> Result<Self> {
+        let wasm = wasmer::wat2wasm(source.as_bytes())?;
+        Self::from_wasm(&wasm, evm_api, evm_data, compile, config)
+    }
+
+    pub fn from_wasm(
+        wasm: &[u8],
+        evm_api: E,
+        evm_data: EvmData,
+        compile: &CompileConfig,
+        config: StylusConfig,
+    ) -> Result<Self> {
+        Self::from_wasm_with_imports(wasm, evm_api, evm_data, compile, config, no_extra_imports)
+    }
+
+    /// Like `from_wasm`, but merges in host functions beyond the built-in hostios.
+    /// The closure may not define imports that collide with those the instance already provides.
+    pub fn from_wasm_with_imports<F>(
+        wasm: &[u8],
+        evm_api: E,
+        evm_data: EvmData,
+        compile: &CompileConfig,
+        config: StylusConfig,
+        extra: F,
+    ) -> Result<Self>
+    where
+        F: FnOnce(&mut Store, &FunctionEnv<WasmEnv<E>>) -> Imports,
+    {
         let env = WasmEnv::new(compile.clone(), Some(config), evm_api, evm_data);
         let store = env.compile.store();
-        let wat_or_wasm = std::fs::read(path)?;
-        let module = Module::new(&store, wat_or_wasm)?;
-        Self::from_module(module, store, env)
+        let module = Module::new(&store, wasm)?;
+        Self::from_module(module, store, env, extra)
     }
 
-    fn from_module(module: Module, mut store: Store, env: WasmEnv<E>) -> Result<Self> {
+    fn from_module<F>(module: Module, mut store: Store, env: WasmEnv<E>, extra: F) -> Result<Self>
+    where
+        F: FnOnce(&mut Store, &FunctionEnv<WasmEnv<E>>) -> Imports,
+    {
         let debug_funcs = env.compile.debug.debug_funcs;
         let func_env = FunctionEnv::new(&mut store, env);
         macro_rules! func {
@@ -119,7 +297,10 @@ impl<E: EvmApi> NativeInstance<E> {
         let mut imports = imports! {
             "vm_hooks" => {
                 "read_args" => func!(host::read_args),
+                "read_args_slice" => func!(host::read_args_slice),
+                "args_len" => func!(host::args_len),
                 "write_result" => func!(host::write_result),
+                "abort" => func!(host::abort),
                 "storage_load_bytes32" => func!(host::storage_load_bytes32),
                 "storage_store_bytes32" => func!(host::storage_store_bytes32),
                 "call_contract" => func!(host::call_contract),
@@ -127,14 +308,22 @@ impl<E: EvmApi> NativeInstance<E> {
                 "static_call_contract" => func!(host::static_call_contract),
                 "create1" => func!(host::create1),
                 "create2" => func!(host::create2),
+                "create2_address" => func!(host::create2_address),
                 "read_return_data" => func!(host::read_return_data),
+                "read_return_data_slice" => func!(host::read_return_data_slice),
                 "return_data_size" => func!(host::return_data_size),
                 "emit_log" => func!(host::emit_log),
+                "emit_log_separate" => func!(host::emit_log_separate),
                 "account_balance" => func!(host::account_balance),
                 "account_codehash" => func!(host::account_codehash),
+                "account_storage_root" => func!(host::account_storage_root),
+                "account_nonce" => func!(host::account_nonce),
+                "selfdestruct" => func!(host::selfdestruct),
                 "evm_gas_left" => func!(host::evm_gas_left),
                 "evm_ink_left" => func!(host::evm_ink_left),
                 "block_basefee" => func!(host::block_basefee),
+                "block_prevrandao" => func!(host::block_prevrandao),
+                "block_blob_basefee" => func!(host::block_blob_basefee),
                 "chainid" => func!(host::chainid),
                 "block_coinbase" => func!(host::block_coinbase),
                 "block_gas_limit" => func!(host::block_gas_limit),
@@ -147,12 +336,19 @@ impl<E: EvmApi> NativeInstance<E> {
                 "tx_gas_price" => func!(host::tx_gas_price),
                 "tx_ink_price" => func!(host::tx_ink_price),
                 "tx_origin" => func!(host::tx_origin),
+                "tx_type" => func!(host::tx_type),
                 "memory_grow" => func!(host::memory_grow),
                 "native_keccak256" => func!(host::native_keccak256),
+                "lib_bn254_add" => func!(host::lib_bn254_add),
+                "lib_bn254_mul" => func!(host::lib_bn254_mul),
+                "lib_bn254_pairing" => func!(host::lib_bn254_pairing),
+                "lib_blake2f" => func!(host::lib_blake2f),
             },
         };
         if debug_funcs {
             imports.define("console", "log_txt", func!(host::console_log_text));
+            imports.define("console", "log_bytes", func!(host::console_log_bytes));
+            imports.define("console", "log_leveled", func!(host::console_log_leveled));
             imports.define("console", "log_i32", func!(host::console_log::<E, u32>));
             imports.define("console", "log_i64", func!(host::console_log::<E, u64>));
             imports.define("console", "log_f32", func!(host::console_log::<E, f32>));
@@ -162,6 +358,17 @@ impl<E: EvmApi> NativeInstance<E> {
             imports.define("console", "tee_f32", func!(host::console_tee::<E, f32>));
             imports.define("console", "tee_f64", func!(host::console_tee::<E, f64>));
             imports.define("debug", "null_host", func!(host::null_host));
+            imports.define("debug", "read_preimage", func!(host::read_preimage));
+            imports.define("debug", "fuel_checkpoint", func!(host::fuel_checkpoint));
+        }
+        for ((space, name), import) in &extra(&mut store, &func_env) {
+            if imports.exists(&space, &name) {
+                bail!(
+                    "extra import {} collides with a built-in hostio",
+                    format!("{space}::{name}").red()
+                );
+            }
+            imports.define(&space, &name, import);
         }
         let instance = Instance::new(&mut store, &module, &imports)?;
         let exports = &instance.exports;
@@ -264,10 +471,10 @@ impl<E: EvmApi> CountingMachine for NativeInstance<E> {
     fn operator_counts(&mut self) -> Result<BTreeMap<OperatorCode, u64>> {
         let mut counts = BTreeMap::new();
 
-        for (&op, &offset) in OP_OFFSETS.lock().iter() {
+        for offset in 0..Counter::SLOTS {
             let count: u64 = self.get_global(&Counter::global_name(offset))?;
             if count != 0 {
-                counts.insert(op, count);
+                counts.insert(OperatorCode::from_dense_index(offset), count);
             }
         }
         Ok(counts)
@@ -288,12 +495,80 @@ impl<E: EvmApi> StartlessMachine for NativeInstance<E> {
     fn get_start(&self) -> Result<TypedFunction<(), ()>> {
         let store = &self.store;
         let exports = &self.instance.exports;
+
+        // the start mover records the name it chose
+        let module = self.instance.module();
+        let Some(name) = module.custom_sections(start::STYLUS_START).next() else {
+            bail!("missing start function");
+        };
+        let name = String::from_utf8(name.into())?;
         exports
-            .get_typed_function(store, STYLUS_START)
+            .get_typed_function(store, &name)
             .map_err(ErrReport::new)
     }
 }
 
+fn no_extra_imports<E: EvmApi>(_: &mut Store, _: &FunctionEnv<WasmEnv<E>>) -> Imports {
+    Imports::new()
+}
+
+/// Marks the start of every serialized module.
+const MODULE_MAGIC: [u8; 4] = *b"\0sty";
+
+/// Identifies what produced a serialized module: the magic, the wasmer version, and the config's
+/// fingerprint, with the version prefixed by its length.
+fn module_header(compile: &CompileConfig) -> Vec<u8> {
+    let version = wasmer::VERSION.as_bytes();
+    let mut header = MODULE_MAGIC.to_vec();
+    header.push(version.len() as u8);
+    header.extend(version);
+    header.extend(compile.fingerprint());
+    header
+}
+
+/// Strips the header prepended to a serialized module, checking it against `compile`.
+fn check_header<'a>(module: &'a [u8], compile: &CompileConfig) -> Result<&'a [u8]> {
+    let (fingerprint, module) = split_header(module)?;
+    let expected = compile.fingerprint();
+    if fingerprint != &expected[..] {
+        bail!(
+            "module was compiled under a different config: {} vs {}",
+            hex::encode(fingerprint).red(),
+            hex::encode(&expected[..]).red(),
+        );
+    }
+    Ok(module)
+}
+
+/// Splits a serialized module into its config fingerprint and body, checking the magic and version.
+fn split_header(module: &[u8]) -> Result<(&[u8], &[u8])> {
+    let Some(module) = module.strip_prefix(&MODULE_MAGIC) else {
+        bail!("not a serialized stylus module");
+    };
+    let Some((&len, module)) = module.split_first() else {
+        bail!("module is missing its wasmer version");
+    };
+    if module.len() < len as usize + 32 {
+        bail!("module header is truncated");
+    }
+    let (version, module) = module.split_at(len.into());
+    if version != wasmer::VERSION.as_bytes() {
+        bail!(
+            "module was serialized by wasmer {} but this is {}",
+            String::from_utf8_lossy(version).red(),
+            wasmer::VERSION.red(),
+        );
+    }
+    Ok(module.split_at(32))
+}
+
+/// Computes the hash a serialized module is cached under, without deserializing it.
+pub fn module_hash(module: &[u8]) -> Result<Bytes32> {
+    let (_, module) = split_header(module)?;
+    Ok(ModuleCache::hash(module))
+}
+
+/// Compiles a module, prefixing the serialized result with a header identifying its origin.
 pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
     let mut store = compile.store();
     let module = Module::new(&store, wasm)?;
@@ -320,7 +595,10 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
     let mut imports = imports! {
         "vm_hooks" => {
             "read_args" => stub!(|_: u32|),
+            "read_args_slice" => stub!(|_: u32, _: u32, _: u32|),
+            "args_len" => stub!(u32 <- ||),
             "write_result" => stub!(|_: u32, _: u32|),
+            "abort" => stub!(|_: u32, _: u32|),
             "storage_load_bytes32" => stub!(|_: u32, _: u32|),
             "storage_store_bytes32" => stub!(|_: u32, _: u32|),
             "call_contract" => stub!(u8 <- |_: u32, _: u32, _: u32, _: u32, _: u64, _: u32|),
@@ -328,14 +606,22 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
             "static_call_contract" => stub!(u8 <- |_: u32, _: u32, _: u32, _: u64, _: u32|),
             "create1" => stub!(|_: u32, _: u32, _: u32, _: u32, _: u32|),
             "create2" => stub!(|_: u32, _: u32, _: u32, _: u32, _: u32, _: u32|),
+            "create2_address" => stub!(|_: u32, _: u32, _: u32|),
             "read_return_data" => stub!(u32 <- |_: u32, _: u32, _: u32|),
+            "read_return_data_slice" => stub!(|_: u32, _: u32, _: u32|),
             "return_data_size" => stub!(u32 <- ||),
             "emit_log" => stub!(|_: u32, _: u32, _: u32|),
+            "emit_log_separate" => stub!(|_: u32, _: u32, _: u32, _: u32|),
             "account_balance" => stub!(|_: u32, _: u32|),
             "account_codehash" => stub!(|_: u32, _: u32|),
+            "account_storage_root" => stub!(|_: u32, _: u32|),
+            "account_nonce" => stub!(u64 <- |_: u32|),
+            "selfdestruct" => stub!(|_: u32|),
             "evm_gas_left" => stub!(u64 <- ||),
             "evm_ink_left" => stub!(u64 <- ||),
             "block_basefee" => stub!(|_: u32|),
+            "block_prevrandao" => stub!(|_: u32|),
+            "block_blob_basefee" => stub!(|_: u32|),
             "chainid" => stub!(u64 <- ||),
             "block_coinbase" => stub!(|_: u32|),
             "block_gas_limit" => stub!(u64 <- ||),
@@ -348,12 +634,19 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
             "tx_gas_price" => stub!(|_: u32|),
             "tx_ink_price" => stub!(u32 <- ||),
             "tx_origin" => stub!(|_: u32|),
+            "tx_type" => stub!(u32 <- ||),
             "memory_grow" => stub!(|_: u16|),
             "native_keccak256" => stub!(|_: u32, _: u32, _: u32|),
+            "lib_bn254_add" => stub!(u8 <- |_: u32, _: u32|),
+            "lib_bn254_mul" => stub!(u8 <- |_: u32, _: u32|),
+            "lib_bn254_pairing" => stub!(u8 <- |_: u32, _: u32, _: u32|),
+            "lib_blake2f" => stub!(u8 <- |_: u32, _: u32, _: u32|),
         },
     };
     if compile.debug.debug_funcs {
         imports.define("console", "log_txt", stub!(|_: u32, _: u32|));
+        imports.define("console", "log_bytes", stub!(|_: u32, _: u32|));
+        imports.define("console", "log_leveled", stub!(|_: u32, _: u32, _: u32|));
         imports.define("console", "log_i32", stub!(|_: u32|));
         imports.define("console", "log_i64", stub!(|_: u64|));
         imports.define("console", "log_f32", stub!(|_: f32|));
@@ -363,9 +656,16 @@ pub fn module(wasm: &[u8], compile: CompileConfig) -> Result<Vec<u8>> {
         imports.define("console", "tee_f32", stub!(f32 <- |_: f32|));
         imports.define("console", "tee_f64", stub!(f64 <- |_: f64|));
         imports.define("debug", "null_host", stub!(||));
+        imports.define(
+            "debug",
+            "read_preimage",
+            stub!(u32 <- |_: u32, _: u32, _: u32, _: u32|),
+        );
+        imports.define("debug", "fuel_checkpoint", stub!(||));
     }
     Instance::new(&mut store, &module, &imports)?;
 
+    let header = module_header(&compile);
     let module = module.serialize()?;
-    Ok(module.to_vec())
+    Ok([&header[..], &module].concat())
 }
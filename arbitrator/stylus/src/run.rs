@@ -7,6 +7,7 @@ use arbutil::evm::user::UserOutcome;
 use eyre::{eyre, Result};
 use prover::machine::Machine;
 use prover::programs::{prelude::*, STYLUS_ENTRY_POINT};
+use wasmer::RuntimeError;
 
 pub trait RunProgram {
     fn run_main(&mut self, args: &[u8], config: StylusConfig, ink: u64) -> Result<UserOutcome>;
@@ -48,7 +49,10 @@ impl RunProgram for Machine {
             if self.stack_left() == 0 {
                 return UserOutcome::OutOfStack;
             }
-            UserOutcome::Failure(error)
+            match self.error_pc() {
+                Some(pc) => UserOutcome::Failure(error.wrap_err(format!("trapped at {pc}"))),
+                None => UserOutcome::Failure(error),
+            }
         });
 
         let outs_ptr = call!("user_test", "get_outs_ptr", vec![]);
@@ -73,7 +77,13 @@ impl<E: EvmApi> RunProgram for NativeInstance<E> {
         let env = self.env.as_mut(store);
         env.args = args.to_owned();
         env.outs.clear();
+        env.evm_memory_words = 0;
+        env.hostio_gas.clear();
         env.config = Some(config);
+        let debug = env.compile.debug.debug_funcs;
+        if let Some(checkpoint) = &mut env.fuel_checkpoint {
+            checkpoint.reset(ink);
+        }
 
         let exports = &self.instance.exports;
         let main = exports.get_typed_function::<u32, u32>(store, STYLUS_ENTRY_POINT)?;
@@ -89,10 +99,22 @@ impl<E: EvmApi> RunProgram for NativeInstance<E> {
 
                 let escape: Escape = match outcome.downcast() {
                     Ok(escape) => escape,
-                    Err(error) => return Ok(Failure(eyre!(error).wrap_err("hard user error"))),
+                    Err(error) => {
+                        // only debug chains see where the trap happened, keeping on-chain errors stable
+                        let context = trap_context(&error);
+                        let mut error = eyre!(error).wrap_err("hard user error");
+                        if let Some(context) = context.filter(|_| debug) {
+                            error = error.wrap_err(context);
+                        }
+                        return Ok(Failure(error));
+                    }
                 };
                 return Ok(match escape {
                     Escape::OutOfInk => OutOfInk,
+                    Escape::Interrupted => Interrupted,
+                    Escape::Revert => Revert(self.env().outs.clone()),
+                    Escape::OutOfMemory => OutOfMemory,
+                    Escape::Exit => Success(self.env().outs.clone()),
                     Escape::Memory(error) => UserOutcome::Failure(error.into()),
                     Escape::Internal(error) | Escape::Logical(error) => UserOutcome::Failure(error),
                 });
@@ -106,3 +128,14 @@ impl<E: EvmApi> RunProgram for NativeInstance<E> {
         })
     }
 }
+
+/// Describes the innermost frame of a trap, which points at the offending instruction.
+fn trap_context(error: &RuntimeError) -> Option<String> {
+    let frame = error.trace().first()?;
+    let func = frame.func_index();
+    let offset = frame.func_offset().unwrap_or(frame.module_offset());
+    let name = frame.function_name().unwrap_or("<unknown>");
+    Some(format!(
+        "trapped in func {func} ({name}) at offset {offset:#x}"
+    ))
+}
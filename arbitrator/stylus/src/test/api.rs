@@ -3,13 +3,17 @@
 
 use crate::{native, run::RunProgram};
 use arbutil::{
-    evm::{api::EvmApi, user::UserOutcomeKind, EvmData},
+    crypto,
+    evm::{self, api::EvmApi, user::UserOutcomeKind, EvmData},
     Bytes20, Bytes32,
 };
 use eyre::Result;
 use parking_lot::Mutex;
 use prover::programs::{memory::MemoryModel, prelude::*};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use super::TestInstance;
 
@@ -23,6 +27,20 @@ pub(crate) struct TestEvmApi {
     configs: Arc<Mutex<HashMap<Bytes20, StylusConfig>>>,
     evm_data: EvmData,
     pages: Arc<Mutex<(u16, u16)>>,
+    access: Arc<Mutex<AccessSets>>,
+    logs: Arc<Mutex<Vec<(Vec<u8>, u32)>>>,
+    balances: Arc<Mutex<HashMap<Bytes20, u64>>>,
+    nonces: Arc<Mutex<HashMap<Bytes20, u64>>>,
+}
+
+/// The slots and accounts touched so far, as tracked by EIP-2929, alongside the refund counter.
+/// These are shared across nested calls, so each `TestEvmApi` acts as a single transaction.
+#[derive(Debug, Default)]
+struct AccessSets {
+    slots: HashSet<(Bytes20, Bytes32)>,
+    accounts: HashSet<Bytes20>,
+    originals: HashMap<(Bytes20, Bytes32), Bytes32>,
+    refund: i64,
 }
 
 impl TestEvmApi {
@@ -42,6 +60,10 @@ impl TestEvmApi {
             configs: Arc::new(Mutex::new(HashMap::new())),
             evm_data,
             pages: Arc::new(Mutex::new((0, 0))),
+            access: Arc::new(Mutex::new(AccessSets::default())),
+            logs: Arc::new(Mutex::new(vec![])),
+            balances: Arc::new(Mutex::new(HashMap::new())),
+            nonces: Arc::new(Mutex::new(HashMap::new())),
         };
         (api, evm_data)
     }
@@ -55,6 +77,68 @@ impl TestEvmApi {
         Ok(())
     }
 
+    pub fn deploy_wat(&mut self, address: Bytes20, config: StylusConfig, wat: &str) -> Result<()> {
+        let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+        let module = native::module(&wasm, self.compile.clone())?;
+        self.contracts.lock().insert(address, module);
+        self.configs.lock().insert(address, config);
+        Ok(())
+    }
+
+    pub fn set_return_data(&mut self, data: &[u8]) {
+        *self.write_result.lock() = data.to_vec();
+    }
+
+    /// The logs emitted thus far, each of which is the topics and data alongside the topic count.
+    pub fn logs(&self) -> Vec<(Vec<u8>, u32)> {
+        self.logs.lock().clone()
+    }
+
+    /// The balance of the given account, which starts at zero.
+    pub fn balance(&self, address: Bytes20) -> u64 {
+        self.balances
+            .lock()
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_balance(&mut self, address: Bytes20, balance: u64) {
+        self.balances.lock().insert(address, balance);
+    }
+
+    /// The nonce of the given account, which starts at zero and counts `create1` deployments.
+    pub fn nonce(&self, address: Bytes20) -> u64 {
+        self.nonces
+            .lock()
+            .get(&address)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Starts a new transaction, cooling all slots and accounts and clearing the refund counter.
+    pub fn reset_access(&self) {
+        *self.access.lock() = AccessSets::default();
+    }
+
+    /// The gas refunded thus far, before applying the EIP-3529 cap.
+    pub fn gas_refund(&self) -> u64 {
+        self.access.lock().refund.max(0) as u64
+    }
+
+    /// Warms the slot, returning whether it was cold.
+    fn touch_slot(&self, key: Bytes32) -> bool {
+        self.access.lock().slots.insert((self.program, key))
+    }
+
+    /// Warms the account, returning the EIP-2929 access cost.
+    fn touch_account(&self, address: Bytes20) -> u64 {
+        match self.access.lock().accounts.insert(address) {
+            true => evm::COLD_ACCOUNT_GAS,
+            false => evm::WARM_ACCESS_GAS,
+        }
+    }
+
     pub fn set_pages(&mut self, open: u16) {
         let mut pages = self.pages.lock();
         pages.0 = open;
@@ -67,14 +151,26 @@ impl EvmApi for TestEvmApi {
         let storage = &mut self.storage.lock();
         let storage = storage.get_mut(&self.program).unwrap();
         let value = storage.get(&key).cloned().unwrap_or_default();
-        (value, 2100) // pretend worst case
+        match self.touch_slot(key) {
+            true => (value, evm::COLD_SLOAD_GAS),
+            false => (value, evm::WARM_ACCESS_GAS),
+        }
     }
 
     fn set_bytes32(&mut self, key: Bytes32, value: Bytes32) -> Result<u64> {
         let storage = &mut self.storage.lock();
         let storage = storage.get_mut(&self.program).unwrap();
-        storage.insert(key, value);
-        Ok(22100) // pretend worst case
+        let current = storage.insert(key, value).unwrap_or_default();
+        let cold = self.touch_slot(key);
+
+        let access = &mut self.access.lock();
+        let original = *access
+            .originals
+            .entry((self.program, key))
+            .or_insert(current);
+        let (cost, refund) = evm::sstore_cost(original, current, value, cold);
+        access.refund += refund;
+        Ok(cost)
     }
 
     /// Simulates a contract call.
@@ -126,23 +222,35 @@ impl EvmApi for TestEvmApi {
         self.contract_call(contract, calldata, gas, Bytes32::default())
     }
 
+    /// Simulates a deployment by bumping the nonce, without running the init code.
+    /// Note: this create function is for testing purposes only and deviates from onchain behavior.
     fn create1(
         &mut self,
         _code: Vec<u8>,
         _endowment: Bytes32,
         _gas: u64,
     ) -> (Result<Bytes20>, u32, u64) {
-        unimplemented!("create1 not supported")
+        let mut nonces = self.nonces.lock();
+        let nonce = nonces.entry(self.program).or_default();
+        let address = create1_address(self.program, *nonce);
+        *nonce += 1;
+        (Ok(address), 0, 0)
     }
 
     fn create2(
         &mut self,
-        _code: Vec<u8>,
+        code: Vec<u8>,
         _endowment: Bytes32,
-        _salt: Bytes32,
+        salt: Bytes32,
         _gas: u64,
     ) -> (Result<Bytes20>, u32, u64) {
-        unimplemented!("create2 not supported")
+        let code_hash = crypto::keccak(code).into();
+        *self.nonces.lock().entry(self.program).or_default() += 1;
+        (
+            Ok(evm::create2_address(self.program, salt, code_hash)),
+            0,
+            0,
+        )
     }
 
     fn get_return_data(&mut self, offset: u32, size: u32) -> Vec<u8> {
@@ -154,16 +262,36 @@ impl EvmApi for TestEvmApi {
         .to_vec()
     }
 
-    fn emit_log(&mut self, _data: Vec<u8>, _topics: u32) -> Result<()> {
-        Ok(()) // pretend a log was emitted
+    fn emit_log(&mut self, data: Vec<u8>, topics: u32) -> Result<()> {
+        self.logs.lock().push((data, topics));
+        Ok(())
     }
 
-    fn account_balance(&mut self, _address: Bytes20) -> (Bytes32, u64) {
-        unimplemented!()
+    fn account_balance(&mut self, address: Bytes20) -> (Bytes32, u64) {
+        (self.balance(address).into(), self.touch_account(address))
     }
 
-    fn account_codehash(&mut self, _address: Bytes20) -> (Bytes32, u64) {
-        unimplemented!()
+    fn account_nonce(&mut self, address: Bytes20) -> (u64, u64) {
+        (self.nonce(address), self.touch_account(address))
+    }
+
+    fn account_codehash(&mut self, address: Bytes20) -> (Bytes32, u64) {
+        (Bytes32::default(), self.touch_account(address)) // pretend all accounts are empty
+    }
+
+    /// Hashes the account's sorted storage entries, standing in for a real trie root.
+    fn account_storage_root(&mut self, address: Bytes20) -> (Bytes32, u64) {
+        let storage = self.storage.lock();
+        let mut entries: Vec<_> = storage.get(&address).into_iter().flatten().collect();
+        entries.sort_by_key(|(key, _)| key.0);
+
+        let mut preimage = vec![];
+        for (key, value) in entries {
+            preimage.extend(key.0);
+            preimage.extend(value.0);
+        }
+        drop(storage);
+        (crypto::keccak(preimage).into(), self.touch_account(address))
     }
 
     fn add_pages(&mut self, new: u16) -> u64 {
@@ -175,4 +303,31 @@ impl EvmApi for TestEvmApi {
         pages.1 = pages.1.max(pages.0);
         model.gas_cost(new, open, ever)
     }
+
+    fn selfdestruct(&mut self, beneficiary: Bytes20) -> Result<u64> {
+        let cost = match self.touch_account(beneficiary) {
+            evm::COLD_ACCOUNT_GAS => evm::SELFDESTRUCT_GAS + evm::COLD_ACCOUNT_GAS,
+            _ => evm::SELFDESTRUCT_GAS,
+        };
+        let mut balances = self.balances.lock();
+        let balance = balances.remove(&self.program).unwrap_or_default();
+        *balances.entry(beneficiary).or_default() += balance;
+        Ok(cost)
+    }
+}
+
+/// The address `CREATE` deploys to, which is the hash of the RLP-encoded sender and nonce.
+fn create1_address(sender: Bytes20, nonce: u64) -> Bytes20 {
+    let nonce_bytes = nonce.to_be_bytes();
+    let nonce_bytes = &nonce_bytes[nonce.leading_zeros() as usize / 8..];
+    let mut nonce_rlp = match nonce {
+        1..=0x7f => vec![],
+        _ => vec![0x80 + nonce_bytes.len() as u8],
+    };
+    nonce_rlp.extend(nonce_bytes);
+
+    let mut rlp = vec![0xc0 + 21 + nonce_rlp.len() as u8, 0x94];
+    rlp.extend(sender.as_slice());
+    rlp.extend(nonce_rlp);
+    crypto::keccak(rlp)[12..].try_into().unwrap()
 }
@@ -3,13 +3,32 @@
 
 use super::test_configs;
 use crate::{
-    env::{Escape, MaybeEscape},
+    env::{ConsoleLevel, ConsoleMessage, Escape, FuelCheckpoint, MaybeEscape},
     native::NativeInstance,
-    test::{check_instrumentation, new_test_machine},
+    run::RunProgram,
+    test::{
+        api::TestEvmApi, check_instrumentation, new_test_machine, new_test_machine_from_binary,
+        run_machine,
+    },
+};
+use arbutil::{
+    crypto,
+    evm::{
+        self,
+        user::{UserOutcome, UserOutcomeKind},
+        EvmData,
+    },
+    operator::OperatorCode,
+    Bytes20, Bytes32,
 };
 use eyre::Result;
-use prover::programs::{prelude::*, start::STYLUS_START};
-use wasmer::{imports, Function};
+use parking_lot::Mutex;
+use prover::{
+    binary::{self, WasmBinary},
+    programs::{config::CompileParseLimits, prelude::*, start::STYLUS_START, ModuleMod},
+};
+use std::{path::Path, sync::Arc};
+use wasmer::{imports, wasmparser::Operator, Function, Pages};
 
 #[test]
 fn test_bulk_memory() -> Result<()> {
@@ -67,6 +86,207 @@ fn test_bulk_memory_oob() -> Result<()> {
     check_instrumentation(native, machine)
 }
 
+#[test]
+fn test_unsupported_extensions() -> Result<()> {
+    let (compile, ..) = test_configs();
+    let check = |path: &str, feature: &str| -> Result<()> {
+        let wat = std::fs::read(path)?;
+        let wasm = wasmer::wat2wasm(&wat)?;
+        let hint = format!("compile with -C target-feature=-{feature}");
+        let err = WasmBinary::parse_user(&wasm, u16::MAX, &compile).unwrap_err();
+        assert!(format!("{err:?}").contains(&hint));
+
+        // only user programs are scanned, though the validator may still reject the wasm
+        if let Err(err) = binary::parse(&wasm, Path::new("user")) {
+            assert!(!format!("{err:?}").contains(&hint));
+        }
+        Ok(())
+    };
+    check("tests/bulk-memory-init.wat", "bulk-memory")?;
+    check("tests/reference-types.wat", "reference-types")
+}
+
+#[test]
+fn test_parse_limits() -> Result<()> {
+    let funcs = "(func)".repeat(16);
+    let wat = format!("(module (memory (export \"memory\") 0 0) {funcs})");
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let path = Path::new("user");
+
+    let mut limits = CompileParseLimits::default();
+    binary::parse_with_limits(&wasm, path, &limits)?;
+
+    limits.max_functions = 15;
+    let err = binary::parse_with_limits(&wasm, path, &limits).unwrap_err();
+    assert!(err.to_string().contains("too many wasm functions"));
+    Ok(())
+}
+
+#[test]
+fn test_segment_limits() -> Result<()> {
+    let datas = r#"(data (i32.const 0) "x")"#.repeat(16);
+    let elems = "(elem (i32.const 0) $f)".repeat(16);
+    let wat = format!(
+        r#"(module (memory (export "memory") 1 1) (table 1 funcref) (func $f) {datas} {elems})"#
+    );
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let path = Path::new("user");
+
+    let mut limits = CompileParseLimits::default();
+    binary::parse_with_limits(&wasm, path, &limits)?;
+
+    limits.max_data_segments = 15;
+    let err = binary::parse_with_limits(&wasm, path, &limits).unwrap_err();
+    assert!(err.to_string().contains("too many wasm data segments"));
+
+    limits.max_data_segments = 16;
+    limits.max_element_segments = 15;
+    let err = binary::parse_with_limits(&wasm, path, &limits).unwrap_err();
+    assert!(err.to_string().contains("too many wasm element segments"));
+    Ok(())
+}
+
+#[test]
+fn test_data_exceeds_heap() -> Result<()> {
+    let (mut compile, ..) = test_configs();
+    compile.bounds.heap_bound = Pages(1);
+
+    // two overlapping segments together declare more than a page of data
+    let segment = "x".repeat(40_000);
+    let wat = format!(
+        r#"
+        (module
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "{segment}")
+            (data (i32.const 0) "{segment}")
+            (func (export "user_entrypoint") (param i32) (result i32)
+                i32.const 0))
+        "#
+    );
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let err = WasmBinary::parse_user(&wasm, u16::MAX, &compile).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("data segments exceed the heap bound"));
+
+    // a single segment fits
+    let wat = wat.replacen(&format!(r#"(data (i32.const 0) "{segment}")"#), "", 1);
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    WasmBinary::parse_user(&wasm, u16::MAX, &compile)?;
+    Ok(())
+}
+
+#[test]
+fn test_float_globals() -> Result<()> {
+    let filename = "tests/float-globals.wat";
+    let (compile, _, _) = test_configs();
+
+    let mut native = NativeInstance::new_test(filename, compile.clone())?;
+    assert_eq!(native.get_global::<f32>("single")?, 1.5);
+    assert_eq!(native.get_global::<f64>("double")?, -2.25);
+    native.get_global::<f64>("single").unwrap_err();
+
+    native.set_global("single", 3.5_f32)?;
+    native.set_global("double", 7.75_f64)?;
+    assert_eq!(native.get_global::<f32>("single")?, 3.5);
+    assert_eq!(native.get_global::<f64>("double")?, 7.75);
+
+    let mut machine = new_test_machine(filename, &compile)?;
+    let single: f32 = machine.get_global("single")?.try_into()?;
+    let double: f64 = machine.get_global("double")?.try_into()?;
+    assert_eq!((single, double), (1.5, -2.25));
+    TryInto::<f64>::try_into(machine.get_global("single")?).unwrap_err();
+
+    machine.set_global("single", 3.5_f32.into())?;
+    let single: f32 = machine.get_global("single")?.try_into()?;
+    assert_eq!(single, 3.5);
+    Ok(())
+}
+
+#[test]
+fn test_block_hostios() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "block_prevrandao"   (func $prevrandao   (param i32)))
+            (import "vm_hooks" "block_blob_basefee" (func $blob_basefee (param i32)))
+            (memory (export "memory") 1 1)
+            (func (export "read")
+                (call $prevrandao   (i32.const 0))
+                (call $blob_basefee (i32.const 32))))
+    "#;
+
+    let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    evm_data.block_prevrandao = Bytes32([0xab; 32]);
+    evm_data.block_blob_basefee = Bytes32::from(7_u64);
+
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let exports = &native.instance.exports;
+    let read = exports.get_typed_function::<(), ()>(&native.store, "read")?;
+    native.call_func(read, ink)?;
+
+    let data = native.read_slice("memory", 0, 64)?;
+    assert_eq!(data[..32], evm_data.block_prevrandao[..]);
+    assert_eq!(data[32..], evm_data.block_blob_basefee[..]);
+    Ok(())
+}
+
+#[test]
+fn test_read_return_data_slice() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "read_return_data_slice" (func $read (param i32 i32 i32)))
+            (memory (export "memory") 1 1)
+            (func (export "read") (param $offset i32) (param $size i32)
+                (call $read (i32.const 0) (local.get $offset) (local.get $size))))
+    "#;
+
+    let data: Vec<u8> = (0..64).collect();
+    let (mut evm, mut evm_data) = TestEvmApi::new(compile.clone());
+    evm.set_return_data(&data);
+    evm_data.return_data_len = data.len() as u32;
+
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let exports = &native.instance.exports;
+    let read = exports.get_typed_function::<(i32, i32), ()>(&native.store, "read")?;
+
+    native.set_ink(ink);
+    read.call(&mut native.store, 20, 8)?;
+    assert_eq!(native.read_slice("memory", 0, 8)?, data[20..28]);
+
+    native.set_ink(ink);
+    read.call(&mut native.store, 60, 5).unwrap_err();
+    read.call(&mut native.store, -1, 2).unwrap_err();
+    Ok(())
+}
+
+#[test]
+fn test_emit_log_separate() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "emit_log_separate" (func $emit (param i32 i32 i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "\01\02\03")
+            (data (i32.const 96) "\04")
+            (data (i32.const 256) "log data")
+            (func (export "emit")
+                (call $emit (i32.const 0) (i32.const 3) (i32.const 256) (i32.const 8))))
+    "#;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm.clone(), evm_data, &compile, config)?;
+    let exports = &native.instance.exports;
+    let emit = exports.get_typed_function::<(), ()>(&native.store, "emit")?;
+    native.call_func(emit, ink)?;
+
+    let mut expected = native.read_slice("memory", 0, 3 * 32)?;
+    expected.extend(b"log data");
+    assert_eq!(evm.logs(), vec![(expected, 3)]);
+    Ok(())
+}
+
 #[test]
 fn test_console() -> Result<()> {
     let filename = "tests/console.wat";
@@ -80,3 +300,1058 @@ fn test_console() -> Result<()> {
     machine.call_user_func(STYLUS_START, vec![], ink)?;
     check_instrumentation(native, machine)
 }
+
+#[test]
+fn test_console_log_bytes() -> Result<()> {
+    let (mut compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "console" "log_bytes" (func $log_bytes (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "\de\ad\be\ef")
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $log_bytes (i32.const 0) (i32.const 4))
+                (call $log_bytes (i32.const 2) (i32.const 0))
+                i32.const 0))
+    "#;
+
+    let messages = Arc::new(Mutex::new(vec![]));
+    let sink = messages.clone();
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    native.env_mut().console_sink = Box::new(move |msg: ConsoleMessage| {
+        sink.lock().push(msg.body.to_string());
+    });
+    let outcome = native.run_main(&[], config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+    assert_eq!(*messages.lock(), ["0xdeadbeef", "0x"]);
+
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let mut bin = binary::parse(&wasm, Path::new("user"))?;
+    let stylus_data = bin.instrument(&compile)?;
+    let mut machine = new_test_machine_from_binary(bin, stylus_data, &compile)?;
+    let outcome = machine.run_main(&[], config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+
+    // the hostio is only available in debug mode
+    compile.debug.debug_funcs = false;
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    assert!(NativeInstance::from_wat(wat, evm, evm_data, &compile, config).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_console_levels() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "console" "log_leveled" (func $log (param i32 i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "noisy")
+            (func (export "trace")
+                (call $log (i32.const 0) (i32.const 0) (i32.const 5)))
+            (func (export "error")
+                (call $log (i32.const 4) (i32.const 0) (i32.const 5)))
+            (func (export "invalid")
+                (call $log (i32.const 5) (i32.const 0) (i32.const 5))))
+    "#;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    native.env_mut().console_level = ConsoleLevel::Warn;
+
+    let env = native.env_mut();
+    assert!(!env.say_at(ConsoleLevel::Trace, "dropped"));
+    assert!(!env.say_at(ConsoleLevel::Info, "dropped"));
+    assert!(env.say_at(ConsoleLevel::Warn, "printed"));
+    assert!(env.say_at(ConsoleLevel::Error, "printed"));
+
+    let exports = &native.instance.exports;
+    let trace = exports.get_typed_function::<(), ()>(&native.store, "trace")?;
+    let error = exports.get_typed_function::<(), ()>(&native.store, "error")?;
+    let invalid = exports.get_typed_function::<(), ()>(&native.store, "invalid")?;
+    native.call_func(trace, ink)?;
+    native.call_func(error, ink)?;
+
+    let err = native.call_func(invalid, ink).unwrap_err();
+    assert!(format!("{err:?}").contains("invalid console level"));
+    Ok(())
+}
+
+#[test]
+fn test_console_sink() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "console" "log_txt" (func $log_txt (param i32 i32)))
+            (import "console" "log_bytes" (func $log_bytes (param i32 i32)))
+            (import "console" "log_leveled" (func $log_leveled (param i32 i32 i32)))
+            (import "console" "tee_i32" (func $tee_i32 (param i32) (result i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "hi\ff\00")
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $log_txt (i32.const 0) (i32.const 2))
+                (call $log_bytes (i32.const 1) (i32.const 3))
+                (call $log_leveled (i32.const 0) (i32.const 0) (i32.const 1))
+                (call $log_leveled (i32.const 3) (i32.const 1) (i32.const 1))
+                (drop (call $tee_i32 (i32.const 7)))
+                i32.const 0))
+    "#;
+
+    let messages = Arc::new(Mutex::new(vec![]));
+    let sink = messages.clone();
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    native.env_mut().console_level = ConsoleLevel::Debug;
+    native.env_mut().console_sink = Box::new(move |msg: ConsoleMessage| {
+        sink.lock().push(msg.into_owned());
+    });
+    let outcome = native.run_main(&[], config, ink)?;
+    assert!(matches!(outcome, UserOutcome::Success(_)));
+
+    let text = |level, text: &str| ConsoleMessage::text(level, text.to_owned());
+    let mut tee = text(ConsoleLevel::Info, "7");
+    tee.tee = true;
+
+    let expected = vec![
+        text(ConsoleLevel::Info, "hi"),
+        ConsoleMessage::bytes(ConsoleLevel::Info, vec![0x69, 0xff, 0x00]),
+        text(ConsoleLevel::Warn, "i"),
+        tee,
+    ];
+    assert_eq!(*messages.lock(), expected);
+    assert_eq!(format!("{}", expected[1].body), "0x69ff00");
+    Ok(())
+}
+
+#[test]
+fn test_extra_imports() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "custom" "double" (func $double (param i32) (result i32)))
+            (memory (export "memory") 0 0)
+            (func (export "quadruple") (param i32) (result i32)
+                (call $double (call $double (local.get 0)))))
+    "#;
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wasm_with_imports(
+        &wasm,
+        evm,
+        evm_data,
+        &compile,
+        config,
+        |store, _| {
+            imports! {
+                "custom" => {
+                    "double" => Function::new_typed(store, |x: u32| 2 * x),
+                },
+            }
+        },
+    )?;
+    let exports = &native.instance.exports;
+    let quadruple = exports.get_typed_function::<u32, u32>(&native.store, "quadruple")?;
+
+    native.set_ink(ink);
+    assert_eq!(quadruple.call(&mut native.store, 3)?, 12);
+
+    // built-in hostios can't be replaced
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let err = NativeInstance::from_wasm_with_imports(
+        &wasm,
+        evm,
+        evm_data,
+        &compile,
+        config,
+        |store, _| {
+            imports! {
+                "vm_hooks" => {
+                    "read_args" => Function::new_typed(store, |_: u32| {}),
+                },
+            }
+        },
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("collides"));
+    Ok(())
+}
+
+#[test]
+fn test_retain_exports() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (memory (export "memory") 1 1)
+            (func $helper (export "helper") (result i32)
+                i32.const 0)
+            (func (export "other_helper"))
+            (func (export "user_entrypoint") (param i32) (result i32)
+                call $helper))
+    "#;
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let mut bin = binary::parse(&wasm, Path::new("user"))?;
+
+    let keep = ["helper".to_owned()].into_iter().collect();
+    bin.retain_exports(&keep);
+    let stylus_data = bin.instrument(&compile)?;
+
+    let mut exports: Vec<_> = bin
+        .exports
+        .keys()
+        .filter(|x| !x.starts_with("stylus"))
+        .collect();
+    exports.sort();
+    assert_eq!(exports, ["helper", "memory", "user_entrypoint"]);
+
+    let mut machine = new_test_machine_from_binary(bin, stylus_data, &compile)?;
+    machine
+        .call_user_func("other_helper", vec![], ink)
+        .unwrap_err();
+    assert_eq!(
+        machine.call_user_func("helper", vec![], ink)?,
+        vec![0_u32.into()]
+    );
+    run_machine(&mut machine, &[], config, ink)?;
+    Ok(())
+}
+
+#[test]
+fn test_require_entrypoint() -> Result<()> {
+    let (compile, ..) = test_configs();
+    let check = |wat: &str, expected: &str| -> Result<()> {
+        let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+        let err = WasmBinary::parse_user(&wasm, 128, &compile).unwrap_err();
+        assert!(format!("{err:?}").contains(expected));
+
+        let store = compile.store();
+        let module = wasmer::Module::new(&store, &wasm)?;
+        let err = module.info().require_entrypoint().unwrap_err();
+        assert!(format!("{err:?}").contains(expected));
+        Ok(())
+    };
+
+    let memory = r#"(memory (export "memory") 0 0)"#;
+    let missing = format!(r#"(module {memory} (func (export "main")))"#);
+    let global =
+        format!(r#"(module {memory} (global (export "user_entrypoint") i32 (i32.const 0)))"#);
+    let wrong_ty = format!(r#"(module {memory} (func (export "user_entrypoint")))"#);
+    check(&missing, "missing export with name")?;
+    check(&global, "must be a function")?;
+    check(&wrong_ty, "wrong type")?;
+    Ok(())
+}
+
+#[test]
+fn test_from_wat() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (memory (export "memory") 0 0)
+            (func (export "add_one") (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add))
+    "#;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let exports = &native.instance.exports;
+    let add_one = exports.get_typed_function::<i32, i32>(&native.store, "add_one")?;
+
+    native.set_ink(ink);
+    assert_eq!(add_one.call(&mut native.store, 32)?, 33);
+    assert!(native.ink_ready()? < ink);
+    Ok(())
+}
+
+#[test]
+fn test_op_counts() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let add = OperatorCode::from(Operator::I32Add);
+
+    let run = |adds: usize| -> Result<_> {
+        let body = "i32.const 1 i32.add ".repeat(adds);
+        let wat = format!(
+            r#"(module
+                (memory (export "memory") 0 0)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    i32.const 0 {body} drop
+                    i32.const 0))"#
+        );
+        let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wasm(&wasm, evm, evm_data, &compile, config)?;
+        let outcome = native.run_main(&[], config, ink)?;
+        assert!(matches!(outcome, UserOutcome::Success(_)));
+        native.operator_counts()
+    };
+
+    // the depth checker contributes its own adds, so compare against an empty body
+    let adds = 7;
+    let base = run(0)?.get(&add).copied().unwrap_or_default();
+    let counts = run(adds)?;
+    assert_eq!(counts[&add], base + adds as u64);
+
+    let data = crate::encode_op_counts(&counts);
+    assert_eq!(data.len(), 10 * counts.len());
+    let entry = data.chunks(10).find(|x| x[..2] == [0x00, 0x6a]).unwrap();
+    assert_eq!(entry[2..], counts[&add].to_be_bytes());
+
+    // counting is opt-in, and only honored on debug chains
+    let count_ops = |debug_mode| crate::debug_config(1, debug_mode).debug.count_ops;
+    assert!(!count_ops(crate::DEBUG_CHAIN));
+    assert!(!count_ops(crate::DEBUG_COUNT_OPS));
+    assert!(count_ops(crate::DEBUG_CHAIN | crate::DEBUG_COUNT_OPS));
+    Ok(())
+}
+
+#[test]
+fn test_fuel_checkpoint() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "evm_ink_left" (func $ink_left (result i64)))
+            (memory (export "memory") 0 0)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (loop $spin
+                    (drop (call $ink_left))
+                    (br $spin))
+                i32.const 0))
+    "#;
+
+    let calls = Arc::new(Mutex::new(0));
+    let count = calls.clone();
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    native.env_mut().fuel_checkpoint = Some(FuelCheckpoint::new(10_000, move || {
+        let mut count = count.lock();
+        *count += 1;
+        *count < 4
+    }));
+    let outcome = native.run_main(&[], config, ink)?;
+    assert!(matches!(outcome, UserOutcome::Interrupted));
+    assert_eq!(*calls.lock(), 4);
+    assert!(crate::final_ink(outcome.kind(), &mut native) > 0);
+    Ok(())
+}
+
+#[test]
+fn test_fuel_checkpoint_without_hostios() -> Result<()> {
+    let (mut compile, config, ink) = test_configs();
+    compile.debug.fuel_checks = true;
+    let wat = r#"
+        (module
+            (memory (export "memory") 0 0)
+            (func $spin
+                (loop $spin (br $spin)))
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $spin)
+                i32.const 0))
+    "#;
+
+    let calls = Arc::new(Mutex::new(0));
+    let count = calls.clone();
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    native.env_mut().fuel_checkpoint = Some(FuelCheckpoint::new(10_000, move || {
+        let mut count = count.lock();
+        *count += 1;
+        *count < 4
+    }));
+    let outcome = native.run_main(&[], config, ink)?;
+    assert!(matches!(outcome, UserOutcome::Interrupted));
+    assert_eq!(*calls.lock(), 4);
+    assert!(crate::final_ink(outcome.kind(), &mut native) > 0);
+    Ok(())
+}
+
+#[test]
+fn test_tx_type() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "tx_type" (func $tx_type (result i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (i32.store8 (i32.const 0) (call $tx_type))
+                (call $write_result (i32.const 0) (i32.const 1))
+                i32.const 0))
+    "#;
+
+    for tx_type in [0, 2, 3] {
+        let (evm, mut evm_data) = TestEvmApi::new(compile.clone());
+        evm_data.tx_type = tx_type;
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+        let outcome = native.run_main(&[], config, ink)?;
+        assert_eq!(outcome.into_data().1, vec![tx_type]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_read_args_slice() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let module = |body: &str| {
+        format!(
+            r#"(module
+                (import "vm_hooks" "read_args" (func $read_args (param i32)))
+                (import "vm_hooks" "read_args_slice" (func $read_args_slice (param i32 i32 i32)))
+                (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+                (memory (export "memory") 1 1)
+                (func (export "user_entrypoint") (param $len i32) (result i32)
+                    {body}
+                    i32.const 0))"#
+        )
+    };
+    let full = module(
+        "(call $read_args (i32.const 0)) (call $write_result (i32.const 0) (local.get $len))",
+    );
+    let sliced = module(
+        r#"(call $read_args_slice (i32.const 0) (i32.const 0) (i32.const 4))
+           (call $read_args_slice (i32.const 4) (i32.const 4) (i32.const 32))
+           (call $write_result (i32.const 0) (i32.const 36))"#,
+    );
+    let overrun = module("(call $read_args_slice (i32.const 0) (i32.const 4) (local.get $len))");
+
+    let run = |wat: &str, args: &[u8]| -> Result<UserOutcome> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+        native.run_main(args, config, ink)
+    };
+
+    let args = [&[0xde, 0xad, 0xbe, 0xef][..], &[7; 32]].concat();
+    let expected = run(&full, &args)?.into_data().1;
+    assert_eq!(run(&sliced, &args)?.into_data().1, expected);
+    assert!(matches!(run(&overrun, &args)?, UserOutcome::Failure(_)));
+    Ok(())
+}
+
+#[test]
+fn test_args_len() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "args_len" (func $args_len (result i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (i32.store (i32.const 0) (call $args_len))
+                (call $write_result (i32.const 0) (i32.const 4))
+                i32.const 0))
+    "#;
+
+    for len in [0, 4, 100] {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+        let outcome = native.run_main(&vec![1; len], config, ink)?;
+        assert_eq!(outcome.into_data().1, (len as u32).to_le_bytes());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_selfdestruct() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "selfdestruct" (func $selfdestruct (param i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14")
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $write_result (i32.const 0) (i32.const 20))
+                (call $selfdestruct (i32.const 0))
+
+                ;; selfdestruct ends the call, so nothing below runs
+                (call $write_result (i32.const 0) (i32.const 20))
+                unreachable))
+    "#;
+    let beneficiary = Bytes20(std::array::from_fn(|i| i as u8 + 1));
+    let program = Bytes20::default();
+
+    let (mut evm, evm_data) = TestEvmApi::new(compile.clone());
+    evm.set_balance(program, 1000);
+    evm.set_balance(beneficiary, 1);
+
+    let mut native = NativeInstance::from_wat(wat, evm.clone(), evm_data, &compile, config)?;
+    let outcome = native.run_main(&[], config, ink)?;
+    assert_eq!(outcome.into_data(), (UserOutcomeKind::Success, vec![]));
+    assert_eq!(evm.balance(program), 0);
+    assert_eq!(evm.balance(beneficiary), 1001);
+
+    // the beneficiary was cold, so the surcharge applies
+    let gas = evm::SELFDESTRUCT_GAS + evm::COLD_ACCOUNT_GAS;
+    assert!(native.ink_consumed(ink) > config.pricing.gas_to_ink(gas));
+    Ok(())
+}
+
+#[test]
+fn test_account_nonce() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "account_nonce" (func $account_nonce (param i32) (result i64)))
+            (import "vm_hooks" "create1" (func $create1 (param i32 i32 i32 i32 i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                ;; the program's address is zero, as is the endowment
+                (i64.store (i32.const 100) (call $account_nonce (i32.const 0)))
+                (call $create1 (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 200) (i32.const 220))
+                (i64.store (i32.const 108) (call $account_nonce (i32.const 0)))
+                (call $write_result (i32.const 100) (i32.const 16))
+                i32.const 0))
+    "#;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm.clone(), evm_data, &compile, config)?;
+    let outcome = native.run_main(&[], config, ink)?;
+    let expected = [0_u64.to_le_bytes(), 1_u64.to_le_bytes()].concat();
+    assert_eq!(outcome.into_data().1, expected);
+    assert_eq!(evm.nonce(Bytes20::default()), 1);
+    Ok(())
+}
+
+#[test]
+fn test_create2_address() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "create2" (func $create2 (param i32 i32 i32 i32 i32 i32)))
+            (import "vm_hooks" "create2_address" (func $create2_address (param i32 i32 i32)))
+            (import "vm_hooks" "native_keccak256" (func $keccak (param i32 i32 i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "code")
+            (data (i32.const 64) "salt")
+            (func (export "user_entrypoint") (param i32) (result i32)
+                ;; hash the code, then compare the precomputed address to the deployed one
+                (call $keccak (i32.const 0) (i32.const 4) (i32.const 32))
+                (call $create2_address (i32.const 32) (i32.const 64) (i32.const 200))
+                (call $create2
+                    (i32.const 0) (i32.const 4) (i32.const 128) (i32.const 64) (i32.const 220) (i32.const 240))
+                (call $write_result (i32.const 200) (i32.const 40))
+                i32.const 0))
+    "#;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let outcome = native.run_main(&[], config, ink)?;
+    let data = outcome.into_data().1;
+    assert_eq!(data[..20], data[20..]);
+
+    let mut salt = Bytes32::default();
+    salt[..4].copy_from_slice(b"salt");
+    let code_hash = crypto::keccak(b"code").into();
+    let expected = evm::create2_address(Bytes20::default(), salt, code_hash);
+    assert_eq!(data[..20], expected[..]);
+    Ok(())
+}
+
+#[test]
+fn test_bn254() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let module = |hostio: &str, params: &str, args: &str, out_len: usize| {
+        format!(
+            r#"(module
+                (import "vm_hooks" "read_args" (func $read_args (param i32)))
+                (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+                (import "vm_hooks" "{hostio}" (func $hostio (param {params}) (result i32)))
+                (memory (export "memory") 1 1)
+                (func (export "user_entrypoint") (param $len i32) (result i32)
+                    (call $read_args (i32.const 0))
+                    (if (call $hostio {args} (i32.const 1024))
+                        (then (return (i32.const 1))))
+                    (call $write_result (i32.const 1024) (i32.const {out_len}))
+                    i32.const 0))"#
+        )
+    };
+    let add = module("lib_bn254_add", "i32 i32", "(i32.const 0)", 64);
+    let mul = module("lib_bn254_mul", "i32 i32", "(i32.const 0)", 64);
+    let pairing = module(
+        "lib_bn254_pairing",
+        "i32 i32 i32",
+        "(i32.const 0) (local.get $len)",
+        32,
+    );
+
+    let run = |wat: &str, args: &str| -> Result<UserOutcome> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+        native.run_main(&hex::decode(args)?, config, ink)
+    };
+    let check = |wat: &str, args: &str, expected: &str| -> Result<()> {
+        assert_eq!(hex::encode(run(wat, args)?.into_data().1), expected);
+        Ok(())
+    };
+
+    // the chfast1 vectors come from go-ethereum's bn256Add.json and bn256ScalarMul.json,
+    // while the rest are built from the generators of G1 and G2
+    let g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+              0000000000000000000000000000000000000000000000000000000000000002";
+    let g1_neg = "0000000000000000000000000000000000000000000000000000000000000001\
+                  30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    let g2 = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+              1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+              090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+              12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+    let g1_double = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                     15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+
+    check(&add, &format!("{g1}{g1}"), g1_double)?;
+    check(
+        &add,
+        "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9\
+         063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266\
+         07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed\
+         06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+        "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703\
+         301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+    )?;
+    check(
+        &mul,
+        "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7\
+         21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204\
+         00000000000000000000000000000000000000000000000011138ce750fa15c2",
+        "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c\
+         031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc",
+    )?;
+    check(&mul, &format!("{g1}{:064x}", 2), g1_double)?;
+
+    let one = format!("{:064x}", 1);
+    let zero = format!("{:064x}", 0);
+    check(&pairing, "", &one)?;
+    check(&pairing, &format!("{g1}{g2}{g1_neg}{g2}"), &one)?;
+    check(&pairing, &format!("{g1}{g2}{g1}{g2}"), &zero)?;
+
+    // malformed inputs revert rather than fail
+    let off_curve = format!("{:064x}{:064x}", 1, 3);
+    let revert = |wat: &str, args: &str| -> Result<bool> {
+        Ok(matches!(run(wat, args)?, UserOutcome::Revert(_)))
+    };
+    assert!(revert(&add, &format!("{g1}{off_curve}"))?);
+    assert!(revert(&mul, &format!("{off_curve}{one}"))?);
+    assert!(revert(&pairing, &format!("{g1}{g2}{g1}"))?);
+    Ok(())
+}
+
+#[test]
+fn test_blake2f() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "read_args" (func $read_args (param i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (import "vm_hooks" "lib_blake2f" (func $blake2f (param i32 i32 i32) (result i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param $len i32) (result i32)
+                (call $read_args (i32.const 0))
+                (if (call $blake2f (i32.const 0) (local.get $len) (i32.const 1024))
+                    (then (return (i32.const 1))))
+                (call $write_result (i32.const 1024) (i32.const 64))
+                i32.const 0))
+    "#;
+    let run = |args: &str| -> Result<UserOutcome> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+        native.run_main(&hex::decode(args)?, config, ink)
+    };
+
+    // vectors 4 through 7 from EIP-152, which compress the message "abc"
+    let input = |rounds: u32, last: u8| {
+        let h = "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+                 d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b";
+        let m = format!("616263{}", "00".repeat(125));
+        let t = format!("03{}", "00".repeat(15));
+        format!("{rounds:08x}{h}{m}{t}{last:02x}")
+    };
+    let vectors = [
+        (
+            input(0, 1),
+            "08c9bcf367e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+             d282e6ad7f520e511f6c3e2b8c68059b9442be0454267ce079217e1319cde05b",
+        ),
+        (
+            input(12, 1),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+        ),
+        (
+            input(12, 0),
+            "75ab69d3190a562c51aef8d88f1c2775876944407270c42c9844252c26d28752\
+             98743e7f6d5ea2f2d3e8d226039cd31b4e426ac4f2d3d666a610c2116fde4735",
+        ),
+        (
+            input(1, 1),
+            "b63a380cb2897d521994a85234ee2c181b5f844d2c624c002677e9703449d2fb\
+             a551b3a8333bcdf5f2f7e08993d53923de3d64fcc68c034e717b9293fed7a421",
+        ),
+    ];
+    for (args, expected) in vectors {
+        assert_eq!(hex::encode(run(&args)?.into_data().1), expected);
+    }
+
+    // vectors 0 through 3 are malformed
+    let valid = input(12, 1);
+    let errors = [
+        String::new(),
+        valid[2..].to_owned(),
+        format!("{valid}02"),
+        input(12, 2),
+    ];
+    for args in errors {
+        assert!(matches!(run(&args)?, UserOutcome::Revert(_)));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_evm_data_new() {
+    let (hash, addr) = (Bytes32::default(), Bytes20::default());
+    let new = |chainid, gas_limit, reentrant, tx_type| {
+        EvmData::new(
+            hash, hash, hash, chainid, addr, gas_limit, 1, 2, addr, addr, hash, hash, addr,
+            reentrant, tx_type,
+        )
+    };
+
+    let data = new(42161, 30_000_000, 1, 2).unwrap();
+    assert_eq!(data.chainid, 42161);
+    assert_eq!(data.block_gas_limit, 30_000_000);
+    assert_eq!(data.tx_type, 2);
+    assert_eq!(data.return_data_len, 0);
+    assert!(data.is_reentrant());
+    assert!(!new(42161, 30_000_000, 0, 2).unwrap().is_reentrant());
+    assert!(new(42161, 1, 0, 0x7f).is_ok());
+
+    let err = |data: Result<EvmData>| format!("{:?}", data.unwrap_err());
+    assert!(err(new(0, 30_000_000, 0, 2)).contains("chainid"));
+    assert!(err(new(42161, 0, 0, 2)).contains("gas limit"));
+    assert!(err(new(42161, 30_000_000, 2, 2)).contains("reentrant"));
+    assert!(err(new(42161, 30_000_000, 0, 0x80)).contains("tx type"));
+
+    // FFI paths defer validation to the call, which then fails
+    let data = EvmData::new_unchecked(
+        hash, hash, hash, 0, addr, 30_000_000, 1, 2, addr, addr, hash, hash, addr, 0, 2,
+    );
+    assert_eq!(data.chainid, 0);
+    assert!(data.validate().is_err());
+}
+
+#[test]
+fn test_trap_context() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (memory (export "memory") 0 0)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                nop
+                nop
+                unreachable))
+    "#;
+
+    // the prover reports the offending instruction
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let mut bin = binary::parse(&wasm, Path::new("user"))?;
+    let stylus_data = bin.instrument(&compile)?;
+    let mut machine = new_test_machine_from_binary(bin, stylus_data, &compile)?;
+    let UserOutcome::Failure(error) = machine.run_main(&[], config, ink)? else {
+        panic!("program should trap");
+    };
+    let pc = machine.error_pc().expect("machine should have errored");
+    assert!(format!("{error:?}").contains(&format!("trapped at {pc}")));
+
+    // native debug builds report the trapping function
+    let run_native = |compile: &CompileConfig| -> Result<String> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, compile, config)?;
+        let UserOutcome::Failure(error) = native.run_main(&[], config, ink)? else {
+            panic!("program should trap");
+        };
+        Ok(format!("{error:?}"))
+    };
+    assert!(run_native(&compile)?.contains("trapped in func"));
+
+    // on-chain errors are left unchanged
+    let onchain = CompileConfig::version(compile.version, false);
+    assert!(!run_native(&onchain)?.contains("trapped in func"));
+    Ok(())
+}
+
+#[test]
+fn test_reentrancy_scope() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "read_args"        (func $read_args        (param i32)))
+            (import "vm_hooks" "write_result"     (func $write_result     (param i32 i32)))
+            (import "vm_hooks" "contract_address" (func $contract_address (param i32)))
+            (import "vm_hooks" "return_data_size" (func $return_data_size (result i32)))
+            (import "vm_hooks" "call_contract"
+                (func $call_contract (param i32 i32 i32 i32 i64 i32) (result i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param $len i32) (result i32)
+                ;; recurse into ourselves with all but the first byte
+                (call $read_args (i32.const 0x100))
+                (if (i32.gt_u (local.get $len) (i32.const 1))
+                    (then
+                        (call $contract_address (i32.const 0))
+                        (drop (call $call_contract
+                            (i32.const 0) (i32.const 0x101) (i32.sub (local.get $len) (i32.const 1))
+                            (i32.const 0x40) (i64.const -1) (i32.const 0x60)))
+
+                        ;; the nested call returns one byte less than we were given
+                        (if (i32.ne
+                                (call $return_data_size)
+                                (i32.sub (local.get $len) (i32.const 1)))
+                            (then unreachable))))
+
+                ;; our own args must have survived the nested call
+                (call $read_args (i32.const 0x200))
+                (call $write_result (i32.const 0x200) (local.get $len))
+                i32.const 0))
+    "#;
+
+    let (mut evm, evm_data) = TestEvmApi::new(compile.clone());
+    evm.deploy_wat(evm_data.contract_address, config, wat)?;
+
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let args = [1, 2, 3, 4];
+    let outcome = native.run_main(&args, config, ink)?;
+    assert_eq!(
+        outcome.into_data(),
+        (UserOutcomeKind::Success, args.to_vec())
+    );
+    assert_eq!(native.env().reentrancy_depth(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_memory_expansion() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+
+    let gas_used = |dest: u32| -> Result<u64> {
+        let wat = format!(
+            r#"
+            (module
+                (import "vm_hooks" "read_args_slice" (func $read_args_slice (param i32 i32 i32)))
+                (memory (export "memory") 1 1)
+                (func (export "user_entrypoint") (param $len i32) (result i32)
+                    (call $read_args_slice (i32.const {dest}) (i32.const 0) (local.get $len))
+                    i32.const 0))
+            "#
+        );
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(&wat, evm, evm_data, &compile, config)?;
+        let outcome = native.run_main(&[0; 32], config, ink)?;
+        assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+        assert_eq!(native.env().evm_memory_words, (dest as u64 + 32) / 32);
+
+        let ink_left: u64 = native.ink_left().into();
+        Ok(config.pricing.ink_to_gas(ink - ink_left))
+    };
+
+    // copying into higher offsets grows memory quadratically
+    let costs: Vec<_> = [0, 1024, 8192, 32768]
+        .into_iter()
+        .map(gas_used)
+        .collect::<Result<_>>()?;
+    assert!(costs.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let (low, high) = (costs[0], costs[3]);
+    let expansion = evm::memory_gas(1025) - evm::memory_gas(1);
+    assert!(high - low >= expansion - 1);
+
+    assert_eq!(evm::memory_expansion(4, 0, 64), (4, 0));
+    assert_eq!(evm::memory_expansion(0, 1024, 0), (0, 0));
+    assert_eq!(evm::memory_expansion(0, 0, 32 * 512), (512, 3 * 512 + 512));
+    Ok(())
+}
+
+#[test]
+fn test_memory_expansion_hostios() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+
+    // each hostio copies 32 bytes to $DEST
+    let hostios = [
+        (
+            r#"(import "vm_hooks" "read_args" (func $copy (param i32)))"#,
+            "(call $copy (i32.const $DEST))",
+        ),
+        (
+            r#"(import "vm_hooks" "read_return_data" (func $copy (param i32 i32 i32) (result i32)))"#,
+            "(drop (call $copy (i32.const $DEST) (i32.const 0) (i32.const 32)))",
+        ),
+        (
+            r#"(import "debug" "read_preimage" (func $copy (param i32 i32 i32 i32) (result i32)))"#,
+            "(drop (call $copy (i32.const 0) (i32.const $DEST) (i32.const 0) (i32.const 32)))",
+        ),
+    ];
+
+    for (import, call) in hostios {
+        let gas_used = |dest: u32| -> Result<u64> {
+            let call = call.replace("$DEST", &dest.to_string());
+            let wat = format!(
+                r#"
+                (module
+                    {import}
+                    (memory (export "memory") 1 1)
+                    (func (export "user_entrypoint") (param i32) (result i32)
+                        {call}
+                        i32.const 0))
+                "#
+            );
+            let (evm, evm_data) = TestEvmApi::new(compile.clone());
+            let mut native = NativeInstance::from_wat(&wat, evm, evm_data, &compile, config)?;
+            native.env_mut().preimage_oracle = Some(Box::new(|_| Some(vec![0; 32])));
+
+            let outcome = native.run_main(&[0; 32], config, ink)?;
+            assert_eq!(outcome.kind(), UserOutcomeKind::Success, "{import}");
+            assert_eq!(native.env().evm_memory_words, (dest as u64 + 32) / 32);
+
+            let ink_left: u64 = native.ink_left().into();
+            Ok(config.pricing.ink_to_gas(ink - ink_left))
+        };
+
+        let expansion = evm::memory_gas(1025) - evm::memory_gas(1);
+        let (low, high) = (gas_used(0)?, gas_used(32768)?);
+        assert!(high - low >= expansion - 1, "{import}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_preimage_oracle() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "read_args" (func $read_args (param i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (import "debug" "read_preimage" (func $read_preimage (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $read_args (i32.const 0))
+                (call $write_result
+                    (i32.const 64)
+                    (call $read_preimage (i32.const 0) (i32.const 64) (i32.const 2) (i32.const 100)))
+                i32.const 0))
+    "#;
+
+    let preimage = b"stylus preimage".to_vec();
+    let hash = crypto::keccak(&preimage);
+    let known = preimage.clone();
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    native.env_mut().preimage_oracle = Some(Box::new(move |hash| {
+        (hash == Bytes32::from(crypto::keccak(&known))).then(|| known.clone())
+    }));
+
+    let outcome = native.run_main(&hash, config, ink)?;
+    assert_eq!(
+        outcome.into_data(),
+        (UserOutcomeKind::Success, preimage[2..].to_vec())
+    );
+
+    let outcome = native.run_main(&[0; 32], config, ink)?;
+    assert_eq!(outcome.into_data().0, UserOutcomeKind::Failure);
+    Ok(())
+}
+
+#[test]
+fn test_abort() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (import "vm_hooks" "abort" (func $abort (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 0) "ok\de\ad\be\ef")
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $write_result (i32.const 0) (i32.const 2))
+                (call $abort (i32.const 2) (i32.const 4))
+                i32.const 0))
+    "#;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let outcome = native.run_main(&[], config, ink)?;
+    assert_eq!(
+        outcome.into_data(),
+        (UserOutcomeKind::Revert, vec![0xde, 0xad, 0xbe, 0xef])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_out_of_memory() -> Result<()> {
+    let (_, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "memory_grow" (func (param i32)))
+            (memory (export "memory") 1)
+            (func (export "user_entrypoint") (param $pages i32) (result i32)
+                (drop (memory.grow (local.get $pages)))
+                i32.const 0))
+    "#;
+
+    // args_len doubles as the number of pages to grow by
+    let run = |compile: &CompileConfig, pages: usize| -> Result<UserOutcomeKind> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native = NativeInstance::from_wat(wat, evm, evm_data, compile, config)?;
+        Ok(native.run_main(&vec![0; pages], config, ink)?.kind())
+    };
+
+    let mut compile = CompileConfig::version(1, true);
+    assert_eq!(compile.bounds.grow_bound, Some(Pages(128)));
+    compile.bounds.grow_bound = Some(Pages(2));
+    assert_eq!(run(&compile, 1)?, UserOutcomeKind::Success);
+    assert_eq!(run(&compile, 2)?, UserOutcomeKind::OutOfMemory);
+
+    // without a bound, only the starting pages are limited
+    compile.bounds.grow_bound = None;
+    assert_eq!(run(&compile, 2)?, UserOutcomeKind::Success);
+    Ok(())
+}
+
+#[test]
+fn test_storage_root() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "read_args" (func $read_args (param i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (import "vm_hooks" "storage_store_bytes32" (func $store (param i32 i32)))
+            (import "vm_hooks" "account_storage_root" (func $root (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $read_args (i32.const 0))
+                (call $root (i32.const 200) (i32.const 64))
+                (call $store (i32.const 0) (i32.const 32))
+                (call $root (i32.const 200) (i32.const 96))
+                (call $write_result (i32.const 64) (i32.const 64))
+                i32.const 0))
+    "#;
+
+    // the args are a key and value to store in the program's own storage, whose address is zero
+    let args: Vec<u8> = [[1; 32], [2; 32]].concat();
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let mut native = NativeInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+    let (kind, roots) = native.run_main(&args, config, ink)?.into_data();
+    assert_eq!(kind, UserOutcomeKind::Success);
+
+    // the stub hashes the sorted storage entries
+    let (before, after) = roots.split_at(32);
+    assert_eq!(before, crypto::keccak(b""));
+    assert_eq!(after, crypto::keccak(&args));
+    assert_ne!(before, after);
+    Ok(())
+}
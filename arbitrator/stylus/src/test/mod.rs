@@ -5,8 +5,9 @@ use crate::{env::WasmEnv, native::NativeInstance, run::RunProgram, test::api::Te
 use arbutil::{evm::user::UserOutcome, Bytes20, Bytes32, Color};
 use eyre::{bail, Result};
 use prover::{
+    binary::WasmBinary,
     machine::GlobalState,
-    programs::{config::SigMap, prelude::*},
+    programs::{config::SigMap, prelude::*, StylusData},
     Machine,
 };
 use rand::prelude::*;
@@ -138,7 +139,14 @@ fn new_test_machine(path: &str, compile: &CompileConfig) -> Result<Machine> {
     let wasm = wasmer::wat2wasm(&wat)?;
     let mut bin = prover::binary::parse(&wasm, Path::new("user"))?;
     let stylus_data = bin.instrument(compile)?;
+    new_test_machine_from_binary(bin, stylus_data, compile)
+}
 
+fn new_test_machine_from_binary(
+    bin: WasmBinary,
+    stylus_data: StylusData,
+    compile: &CompileConfig,
+) -> Result<Machine> {
     let wat = std::fs::read("tests/test.wat")?;
     let wasm = wasmer::wat2wasm(&wat)?;
     let lib = prover::binary::parse(&wasm, Path::new("test"))?;
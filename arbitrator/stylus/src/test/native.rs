@@ -7,10 +7,12 @@
 )]
 
 use crate::{
+    cache::ModuleCache,
+    native,
     run::RunProgram,
     test::{
-        check_instrumentation, random_bytes20, random_bytes32, random_ink, run_machine, run_native,
-        test_compile_config, test_configs, TestInstance,
+        api::TestEvmApi, check_instrumentation, new_test_machine, random_bytes20, random_bytes32,
+        random_ink, run_machine, run_native, test_compile_config, test_configs, TestInstance,
     },
 };
 use arbutil::{
@@ -19,23 +21,34 @@ use arbutil::{
         api::EvmApi,
         user::{UserOutcome, UserOutcomeKind},
     },
-    format, Bytes20, Bytes32, Color,
+    format,
+    operator::OperatorCode,
+    pricing::HostioKind,
+    Bytes20, Bytes32, Color,
 };
 use eyre::{bail, ensure, Result};
 use prover::{
     binary,
     programs::{
+        config::SigMap,
         counter::{Counter, CountingMachine},
+        meter::{ConfigPricer, CostTable, OpcodePricer, STYLUS_INK_LEFT},
         prelude::*,
-        start::StartMover,
+        start::{self, StartMover},
         MiddlewareWrapper, ModuleMod,
     },
     Machine,
 };
-use std::{collections::HashMap, path::Path, sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use wasmer::wasmparser::Operator;
-use wasmer::{CompilerConfig, ExportIndex, Imports, Pages, Store};
+use wasmer::{CompilerConfig, ExportIndex, Imports, MemoryType, Pages, Store};
 use wasmer_compiler_singlepass::Singlepass;
+use wasmer_types::ModuleInfo;
 
 #[test]
 fn test_ink() -> Result<()> {
@@ -71,6 +84,187 @@ fn test_ink() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_cost_table() -> Result<()> {
+    let mut compile = test_compile_config();
+    compile.pricing.costs = super::expensive_add;
+    let mut by_func = TestInstance::new_test("tests/add.wat", compile.clone())?;
+
+    // the table prices operators just as the equivalent function does
+    let table = CostTable::from_codes([(Operator::I32Add.into(), 100)]);
+    let func_pricer = ConfigPricer::Func(super::expensive_add);
+    let table_pricer = ConfigPricer::Table(table.clone());
+    let sigs = SigMap::default();
+    for op in [Operator::I32Add, Operator::I64Add, Operator::Drop] {
+        let price = |pricer: &ConfigPricer| pricer.price(&op, &sigs);
+        assert_eq!(price(&func_pricer), price(&table_pricer));
+    }
+
+    // the table takes precedence over the pricing function
+    compile.pricing.costs = |_, _| 1000;
+    compile.pricing.cost_table = Some(table);
+    let mut by_table = TestInstance::new_test("tests/add.wat", compile.clone())?;
+    let mut machine = new_test_machine("tests/add.wat", &compile)?;
+
+    let ink = 1000;
+    for native in [&mut by_func, &mut by_table] {
+        let exports = &native.exports;
+        let add_one = exports.get_typed_function::<i32, i32>(&native.store, "add_one")?;
+        native.set_ink(ink);
+        assert_eq!(add_one.call(&mut native.store, 32)?, 33);
+        assert_eq!(native.ink_left(), MachineMeter::Ready(ink - 100));
+    }
+    machine.call_user_func("add_one", vec![32_u32.into()], ink)?;
+    assert_eq!(machine.ink_left(), MachineMeter::Ready(ink - 100));
+    Ok(())
+}
+
+#[test]
+fn test_free_ink() -> Result<()> {
+    // in loop.wat
+    //     the `spin` function loops `count` times, returning the number of iterations
+
+    let mut compile = test_compile_config();
+    compile.pricing.costs = |_, _| 100;
+    compile.debug.free_ink = true;
+
+    let count = 1_000_000;
+    let mut native = TestInstance::new_test("tests/loop.wat", compile.clone())?;
+    let spin = native
+        .exports
+        .get_typed_function::<i32, i32>(&native.store, "spin")?;
+    native.set_ink(0);
+    assert_eq!(spin.call(&mut native.store, count)?, count);
+    assert_eq!(native.ink_left(), MachineMeter::Ready(0));
+
+    // free ink is only for debug chains
+    compile.debug.debug_funcs = false;
+    let mut native = TestInstance::new_test("tests/loop.wat", compile)?;
+    let spin = native
+        .exports
+        .get_typed_function::<i32, i32>(&native.store, "spin")?;
+    native.set_ink(10_000_000);
+    assert!(spin.call(&mut native.store, count).is_err());
+    assert_eq!(native.ink_left(), MachineMeter::Exhausted);
+    Ok(())
+}
+
+#[test]
+fn test_exhaustion_zeroes_ink() -> Result<()> {
+    // in loop.wat
+    //     the `spin` function loops `count` times, returning the number of iterations
+
+    let compile = test_compile_config();
+    let count = 1_000_000;
+    let ink = 10_000;
+
+    let mut native = TestInstance::new_test("tests/loop.wat", compile.clone())?;
+    let spin = native
+        .exports
+        .get_typed_function::<i32, i32>(&native.store, "spin")?;
+    native.set_ink(ink);
+    assert!(spin.call(&mut native.store, count).is_err());
+    assert_eq!(native.ink_left(), MachineMeter::Exhausted);
+    assert_eq!(native.get_global::<u64>(STYLUS_INK_LEFT)?, 0);
+
+    let mut machine = new_test_machine("tests/loop.wat", &compile)?;
+    let spin = machine.call_user_func("spin", vec![(count as u32).into()], ink);
+    assert!(spin.is_err());
+    assert_eq!(machine.ink_left(), MachineMeter::Exhausted);
+    assert_eq!(machine.get_global(STYLUS_INK_LEFT)?, 0_u64.into());
+
+    // in exhaust.wat
+    //     `copy` and `fill` touch `len` bytes, while `grow` and `grow_one` add pages
+    //     the former are charged dynamically, as is `grow` since its delta isn't a constant
+
+    let filename = "tests/exhaust.wat";
+    let mut compile = test_compile_config();
+    compile.pricing.memory_copy_ink = 1000;
+    compile.pricing.memory_fill_ink = 1000;
+    compile.pricing.memory_grow_ink = 1_000_000;
+
+    for func in ["copy", "fill", "grow", "grow_one"] {
+        let mut native = TestInstance::new_test(filename, compile.clone())?;
+        let exports = &native.exports;
+        let exhaust = exports.get_typed_function::<i32, ()>(&native.store, func)?;
+        native.set_ink(ink);
+        assert!(exhaust.call(&mut native.store, 1000).is_err());
+        assert_eq!(native.ink_left(), MachineMeter::Exhausted, "{func}");
+        assert_eq!(native.get_global::<u64>(STYLUS_INK_LEFT)?, 0, "{func}");
+
+        let mut machine = new_test_machine(filename, &compile)?;
+        let outcome = machine.call_user_func(func, vec![1000_u32.into()], ink);
+        assert!(outcome.is_err());
+        assert_eq!(machine.ink_left(), MachineMeter::Exhausted, "{func}");
+        assert_eq!(machine.get_global(STYLUS_INK_LEFT)?, 0_u64.into());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_no_float() -> Result<()> {
+    // in float.wat
+    //     the `half` function divides its input by 2 using f32 arithmetic
+
+    let mut compile = test_compile_config();
+    TestInstance::new_test("tests/float.wat", compile.clone())?;
+    new_test_machine("tests/float.wat", &compile)?;
+
+    compile.forbid_floats = true;
+    TestInstance::new_test("tests/add.wat", compile.clone())?;
+    new_test_machine("tests/add.wat", &compile)?;
+
+    let err = TestInstance::new_test("tests/float.wat", compile.clone()).unwrap_err();
+    assert!(format!("{err:?}").contains("F32ConvertI32S"));
+    let err = new_test_machine("tests/float.wat", &compile).unwrap_err();
+    assert!(format!("{err:?}").contains("F32ConvertI32S"));
+
+    for index in 0..OperatorCode::DENSE_SLOTS {
+        let code = OperatorCode::from_dense_index(index);
+        let name = code.to_string();
+        let float = name.contains("F32") || name.contains("F64");
+        assert_eq!(code.is_float(), float, "{name}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_grow_meter() -> Result<()> {
+    // in grow.wat
+    //     `grow` requests a dynamic number of pages, while `grow_three` requests a constant 3
+    //     the memory can't grow, so each call returns -1 after paying for the attempt
+
+    let mut compile = test_compile_config();
+    compile.pricing.costs = |_, _| 0;
+    compile.pricing.memory_grow_ink = 1000;
+
+    let ink = 1_000_000;
+    let mut native = TestInstance::new_test("tests/grow.wat", compile.clone())?;
+    let exports = &native.exports;
+    let grow = exports.get_typed_function::<i32, i32>(&native.store, "grow")?;
+    let grow_three = exports.get_typed_function::<(), i32>(&native.store, "grow_three")?;
+
+    for pages in [0, 1, 7, 100] {
+        native.set_ink(ink);
+        assert_eq!(grow.call(&mut native.store, pages)?, -1);
+        assert_eq!(native.ink_ready()?, ink - pages as u64 * 1000);
+    }
+    native.set_ink(ink);
+    assert_eq!(grow_three.call(&mut native.store)?, -1);
+    assert_eq!(native.ink_ready()?, ink - 3 * 1000);
+
+    native.set_ink(999);
+    assert!(grow.call(&mut native.store, 1).is_err());
+    assert_eq!(native.ink_left(), MachineMeter::Exhausted);
+
+    let mut machine = new_test_machine("tests/grow.wat", &compile)?;
+    machine.call_user_func("grow", vec![7_u32.into()], ink)?;
+    assert_eq!(machine.ink_left(), MachineMeter::Ready(ink - 7 * 1000));
+    machine.call_user_func("grow_three", vec![], ink)?;
+    assert_eq!(machine.ink_left(), MachineMeter::Ready(ink - 3 * 1000));
+    Ok(())
+}
+
 #[test]
 fn test_depth() -> Result<()> {
     // in depth.wat
@@ -113,6 +307,31 @@ fn test_depth() -> Result<()> {
     check(4 * frame_size + frame_size / 2, 4)
 }
 
+#[test]
+fn test_depth_br() -> Result<()> {
+    // in depth-br.wat
+    //    `br_out` branches out of the function from within a block
+    //    `br_if_out` conditionally branches out of the function from its outermost scope
+
+    let mut native = TestInstance::new_test("tests/depth-br.wat", test_compile_config())?;
+    let exports = &native.exports;
+    let br_out = exports.get_typed_function::<(), i32>(&native.store, "br_out")?;
+    let br_if_out = exports.get_typed_function::<i32, i32>(&native.store, "br_if_out")?;
+
+    let space = 1024;
+    native.set_stack(space);
+
+    assert_eq!(br_out.call(&mut native.store)?, 7);
+    assert_eq!(native.stack_left(), space);
+
+    assert_eq!(br_if_out.call(&mut native.store, 1)?, 1);
+    assert_eq!(native.stack_left(), space);
+
+    assert_eq!(br_if_out.call(&mut native.store, 0)?, 2);
+    assert_eq!(native.stack_left(), space);
+    Ok(())
+}
+
 #[test]
 fn test_start() -> Result<()> {
     // in start.wat
@@ -143,6 +362,187 @@ fn test_start() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_start_collision() -> Result<()> {
+    // in start-collision.wat
+    //     the program exports its own `stylus_start`, which sets `status` to 100
+    //     the start function must be moved elsewhere, and increments `status`
+
+    let filename = "tests/start-collision.wat";
+    let moved = start::start_export_name(["stylus_start"])?;
+    assert_eq!(moved, "stylus_start_1");
+
+    let compile = test_compile_config();
+    let ink = random_ink(100_000);
+
+    let mut native = TestInstance::new_test(filename, compile.clone())?;
+    let starter = native.get_start()?;
+    native.call_func(starter, ink)?;
+    assert_eq!(native.get_global::<i32>("status")?, 11);
+
+    let exports = &native.exports;
+    let user_start = exports.get_typed_function::<(), ()>(&native.store, "stylus_start")?;
+    native.call_func(user_start, ink)?;
+    assert_eq!(native.get_global::<i32>("status")?, 100);
+
+    // instrumentation records the name exactly
+    let wasm = wasmer::wat2wasm(&std::fs::read(filename)?)?;
+    let mut bin = binary::parse(&wasm, Path::new(filename))?;
+    let stylus_data = bin.instrument(&compile)?;
+    assert_eq!(stylus_data.start_export, Some(moved.clone()));
+
+    let mut machine = new_test_machine(filename, &compile)?;
+    machine.call_user_func(&moved, vec![], ink)?;
+    assert_eq!(machine.get_global("status")?, 11_u32.into());
+    machine.call_user_func("stylus_start", vec![], ink)?;
+    assert_eq!(machine.get_global("status")?, 100_u32.into());
+    Ok(())
+}
+
+#[test]
+fn test_multi_memory() -> Result<()> {
+    let mut module = ModuleInfo::default();
+    for _ in 0..2 {
+        module
+            .memories
+            .push(MemoryType::new(0, None::<Pages>, false));
+    }
+    assert_eq!(module.memory_count(), 2);
+
+    let err = module.memory_info().unwrap_err().to_string();
+    assert!(err.contains("multi-memory extension not supported"));
+    assert!(err.contains('2'));
+    Ok(())
+}
+
+#[test]
+fn test_final_ink() -> Result<()> {
+    use UserOutcomeKind::*;
+
+    let mut native = TestInstance::new_test("tests/add.wat", test_compile_config())?;
+    let ink = random_ink(1_000_000);
+    native.set_ink(ink);
+
+    for (status, expected) in [
+        (Success, ink),
+        (Revert, ink),
+        (Failure, ink),
+        (Interrupted, ink),
+        (OutOfInk, 0),
+        (OutOfStack, 0),
+    ] {
+        assert_eq!(
+            crate::final_ink(status, &mut native),
+            expected,
+            "{status:?}"
+        );
+    }
+
+    // an exhausted meter returns nothing regardless
+    native.set_meter(MachineMeter::Exhausted);
+    assert_eq!(crate::final_ink(Success, &mut native), 0);
+    Ok(())
+}
+
+#[test]
+fn test_gas_snapshot() -> Result<()> {
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, config, ink) = test_configs();
+    let args = [&[0x01][..], b"speculative"].concat();
+
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    native.set_ink(ink);
+    let snapshot = native.gas_snapshot();
+    assert_eq!(snapshot.meter(), MachineMeter::Ready(ink));
+
+    run_native(&mut native, &args, ink)?;
+    assert!(native.ink_left().ink() < ink);
+    native.restore_gas(snapshot);
+    assert_eq!(native.ink_left(), MachineMeter::Ready(ink));
+
+    // exhaustion is rolled back too
+    let outcome = native.run_main(&args, config, 1)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::OutOfInk);
+    assert_eq!(native.ink_left(), MachineMeter::Exhausted);
+    native.restore_gas(snapshot);
+    assert_eq!(native.ink_left(), MachineMeter::Ready(ink));
+    Ok(())
+}
+
+#[test]
+fn test_run_stats() -> Result<()> {
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, config, ink) = test_configs();
+    let args = [&[0x02][..], b"instrumented"].concat();
+
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    let (outcome, stats) = native.run_main_instrumented(&args, config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+
+    // the stats match querying each global separately
+    assert_eq!(stats.ink_used, ink - native.ink_left().ink());
+    assert_eq!(stats.stack_used, config.max_depth - native.stack_left());
+    assert_eq!(stats.op_counts, Some(native.operator_counts()?));
+    assert!(stats.ink_used > 0);
+
+    // running out of ink uses everything supplied
+    let (outcome, stats) = native.run_main_instrumented(&args, config, 1)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::OutOfInk);
+    assert_eq!(stats.ink_used, 1);
+    Ok(())
+}
+
+#[test]
+fn test_exported_functions() -> Result<()> {
+    use prover::value::{ArbValueType::*, FunctionType};
+
+    let (compile, config, _) = test_configs();
+    let wat = r#"
+        (module
+            (memory (export "memory") 0 0)
+            (global (export "counter") (mut i32) (i32.const 0))
+            (func $internal)
+            (func (export "helper") (param i64 f32) (result i32)
+                i32.const 0)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                i32.const 0))
+    "#;
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let native = TestInstance::from_wat(wat, evm, evm_data, &compile, config)?;
+
+    let expected = [
+        ("helper", FunctionType::new(vec![I64, F32], vec![I32])),
+        ("user_entrypoint", FunctionType::new(vec![I32], vec![I32])),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(name, ty)| (name.to_owned(), ty))
+        .collect();
+    assert_eq!(native.exported_functions()?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_send() -> Result<()> {
+    fn assert_send<T: Send>() {}
+    assert_send::<native::NativeInstance<TestEvmApi>>();
+
+    // instances may be created on one thread and run on another
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let preimage = "°º¤ø,¸,ø¤°º¤ø,¸,ø¤°º¤ø,¸ nyan nyan ~=[,,_,,]:3 nyan nyan";
+    let hash = hex::encode(crypto::keccak(preimage.as_bytes()));
+    let (compile, config, ink) = test_configs();
+
+    let mut args = vec![0x01];
+    args.extend(preimage.as_bytes());
+
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    let worker = std::thread::spawn(move || run_native(&mut native, &args, ink));
+    let output = worker.join().expect("worker panicked")?;
+    assert_eq!(hex::encode(output), hash);
+    Ok(())
+}
+
 #[test]
 fn test_count() -> Result<()> {
     let mut compiler = Singlepass::new();
@@ -173,6 +573,11 @@ fn test_count() -> Result<()> {
     assert_eq!(check(GlobalSet { global_index: 0 }), Some(&7));
     assert_eq!(check(I64Add), Some(&7));
     assert_eq!(check(I64Const { value: 0 }), Some(&7));
+
+    // counters are assigned by opcode, so counts mean the same thing in every process
+    for op in counts.keys() {
+        assert_eq!(OperatorCode::from_dense_index(op.dense_index()), *op);
+    }
     Ok(())
 }
 
@@ -280,6 +685,78 @@ fn test_heap() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_heap_bound() -> Result<()> {
+    let (mut compile, ..) = test_configs();
+    compile.bounds.heap_bound = Pages(128);
+
+    let instrument = |memory: &str| {
+        let wat = format!(
+            r#"(module {memory}
+                (func (export "user_entrypoint") (param i32) (result i32) i32.const 0))"#
+        );
+        let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+        let mut bin = binary::parse(&wasm, Path::new("user"))?;
+        bin.instrument(&compile)
+    };
+
+    let data = instrument(r#"(memory (export "memory") 2 2)"#)?;
+    assert_eq!((data.footprint, data.heap_bound), (2, 128));
+
+    let err = instrument(r#"(memory (export "memory") 200 300)"#).unwrap_err();
+    let err = format!("{err:?}");
+    assert!(err.contains("program requires"));
+    for pages in ["200", "300", "128"] {
+        assert!(err.contains(pages));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_memory_usage() -> Result<()> {
+    // in memory.wat
+    //     the input is the target size and amount to step each `memory.grow`
+    //     the output is the memory size in pages
+
+    let (mut compile, config, ink) = test_configs();
+    compile.bounds.heap_bound = Pages(128);
+
+    let (mut native, _) = TestInstance::new_with_evm("tests/memory.wat", &compile, config)?;
+    let before = native.memory_size();
+    assert_eq!(native.memory_bytes(), before.bytes().0);
+
+    let pages = run_native(&mut native, &[8, 3], ink)?[0];
+    assert_eq!(pages, 8);
+    assert!(native.memory_size() > before);
+    assert_eq!(native.memory_size(), Pages(8));
+    assert_eq!(native.memory_bytes(), 8 * 65536);
+    Ok(())
+}
+
+#[test]
+fn test_hostio_costs() -> Result<()> {
+    // in memory.wat
+    //     the input is the target size and amount to step each `memory.grow`
+    //     `read_args` is called exactly once
+
+    let (compile, config, ink) = test_configs();
+    let args = &[1, 1];
+
+    let run = |read_args_ink: Option<u64>| -> Result<u64> {
+        let (mut native, _) = TestInstance::new_with_evm("tests/memory.wat", &compile, config)?;
+        if let Some(cost) = read_args_ink {
+            native.env_mut().hostio_ink.set(HostioKind::ReadArgs, cost);
+        }
+        run_native(&mut native, args, ink)?;
+        Ok(ink - native.ink_ready()?)
+    };
+
+    let default = run(None)?;
+    let custom = run(Some(HostioKind::ReadArgs.ink() + 1_000_000))?;
+    assert_eq!(custom - default, 1_000_000);
+    Ok(())
+}
+
 #[test]
 fn test_rust() -> Result<()> {
     // in keccak.rs
@@ -301,11 +778,15 @@ fn test_rust() -> Result<()> {
     println!("Exec {}", format::time(start.elapsed()));
     assert_eq!(hex::encode(output), hash);
 
+    let consumed = native.ink_consumed(ink);
+    assert_eq!(consumed + native.ink_ready()?, ink);
+
     let mut machine = Machine::from_user_path(Path::new(filename), &compile)?;
     let start = Instant::now();
     let output = run_machine(&mut machine, &args, config, ink)?;
     assert_eq!(hex::encode(output), hash);
     println!("Exec {}", format::time(start.elapsed()));
+    assert_eq!(machine.ink_consumed(ink), consumed);
 
     check_instrumentation(native, machine)
 }
@@ -422,6 +903,106 @@ fn test_storage() -> Result<()> {
     check_instrumentation(native, machine)
 }
 
+#[test]
+fn test_hostio_gas() -> Result<()> {
+    let filename = "tests/storage/target/wasm32-unknown-unknown/release/storage.wasm";
+    let (compile, config, ink) = test_configs();
+
+    let key = crypto::keccak(filename.as_bytes());
+    let value = crypto::keccak("value".as_bytes());
+    let args = [&[0x01][..], &key, &value].concat();
+
+    let mut native = TestInstance::new_linked(filename, &compile, config)?;
+    run_native(&mut native, &args, ink)?;
+
+    let gas = &native.env().hostio_gas;
+    let (&heaviest, _) = gas
+        .iter()
+        .max_by_key(|(_, &gas)| gas)
+        .expect("no gas recorded");
+    assert_eq!(heaviest, HostioKind::StorageStoreBytes32.name());
+    assert_eq!(gas[heaviest], 22100); // cold sstore of a new value
+
+    let encoded = String::from_utf8(crate::encode_hostio_gas(gas))?;
+    assert!(encoded.starts_with("storage_store_bytes32:22100"));
+
+    // only debug chains keep track
+    let wat = r#"
+        (module
+            (import "vm_hooks" "storage_store_bytes32" (func $store (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (call $store (i32.const 0) (i32.const 32))
+                i32.const 0))
+    "#;
+    let onchain = CompileConfig::version(compile.version, false);
+    let (evm, evm_data) = TestEvmApi::new(onchain.clone());
+    let mut native = TestInstance::from_wat(wat, evm, evm_data, &onchain, config)?;
+    let outcome = native.run_main(&[], config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+    assert!(native.env().hostio_gas.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_storage_access() -> Result<()> {
+    let (compile, _, _) = test_configs();
+    let (mut evm, _) = TestEvmApi::new(compile.clone());
+    let key = Bytes32::from(1_u64);
+    let other = Bytes32::from(2_u64);
+
+    // EIP-2929 charges cold on first touch, then warm
+    assert_eq!(evm.get_bytes32(key).1, 2100);
+    assert_eq!(evm.get_bytes32(key).1, 100);
+    assert_eq!(evm.set_bytes32(key, other)?, 20000);
+    assert_eq!(evm.set_bytes32(other, key)?, 22100);
+    assert_eq!(evm.get_bytes32(other).1, 100);
+
+    let account = Bytes20::default();
+    assert_eq!(evm.account_balance(account).1, 2600);
+    assert_eq!(evm.account_codehash(account).1, 100);
+
+    // each api is its own transaction
+    let (mut evm, _) = TestEvmApi::new(compile);
+    assert_eq!(evm.get_bytes32(key).1, 2100);
+    Ok(())
+}
+
+#[test]
+fn test_storage_refunds() -> Result<()> {
+    let (compile, _, _) = test_configs();
+    let (mut evm, _) = TestEvmApi::new(compile);
+    let key = Bytes32::from(1_u64);
+    let fresh = Bytes32::from(2_u64);
+    let (zero, a, b) = (
+        Bytes32::default(),
+        Bytes32::from(3_u64),
+        Bytes32::from(4_u64),
+    );
+
+    // creating then clearing a slot refunds nearly all of the creation
+    assert_eq!(evm.set_bytes32(fresh, a)?, 22100);
+    assert_eq!(evm.set_bytes32(fresh, zero)?, 100);
+    assert_eq!(evm.gas_refund(), 19900);
+
+    // clear a slot that existed before the transaction
+    assert_eq!(evm.set_bytes32(key, a)?, 22100);
+    evm.reset_access();
+    assert_eq!(evm.set_bytes32(key, zero)?, 5000);
+    assert_eq!(evm.gas_refund(), 4800);
+
+    // restoring it undoes the clear but refunds the reset
+    assert_eq!(evm.set_bytes32(key, a)?, 100);
+    assert_eq!(evm.gas_refund(), 2800);
+
+    // overwriting then setting back
+    evm.reset_access();
+    assert_eq!(evm.set_bytes32(key, b)?, 5000);
+    assert_eq!(evm.set_bytes32(key, a)?, 100);
+    assert_eq!(evm.gas_refund(), 2800);
+    Ok(())
+}
+
 #[test]
 fn test_calls() -> Result<()> {
     // in call.rs
@@ -500,3 +1081,233 @@ fn test_calls() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_module_cache() -> Result<()> {
+    let (compile, config, ink) = test_configs();
+    let wat = r#"
+        (module
+            (import "vm_hooks" "read_args" (func $read_args (param i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (memory (export "memory") 1 1)
+            (data (i32.const 1024) "test_module_cache")
+            (func (export "user_entrypoint") (param $args_len i32) (result i32)
+                (call $read_args (i32.const 0))
+                (call $write_result (i32.const 0) (local.get $args_len))
+                i32.const 0))
+    "#;
+    let wasm = wasmer::wat2wasm(wat.as_bytes())?;
+    let module = native::module(&wasm, compile.clone())?;
+    let hash = native::module_hash(&module)?;
+    let args = vec![0xde, 0xad, 0xbe, 0xef];
+
+    let run = || -> Result<Vec<u8>> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let mut native =
+            unsafe { TestInstance::deserialize(&module, compile.clone(), evm, evm_data)? };
+
+        match native.run_main(&args, config, ink)? {
+            UserOutcome::Success(output) => Ok(output),
+            err => bail!("user program failure: {}", err.red()),
+        }
+    };
+
+    // the data segment makes the module unique, so no other test caches it
+    assert!(ModuleCache::get(hash, &compile).is_none());
+    let cold_output = run()?;
+    assert!(ModuleCache::get(hash, &compile).is_some());
+    let warm_output = run()?;
+    assert_eq!(cold_output, args);
+    assert_eq!(warm_output, args);
+
+    // entries are keyed on the full config, not just its version
+    let mut other = compile.clone();
+    other.pricing.memory_fill_ink += 1;
+    assert_eq!(other.version, compile.version);
+    assert!(ModuleCache::get(hash, &other).is_none());
+    Ok(())
+}
+
+#[cfg(feature = "cranelift")]
+#[test]
+fn test_compiler_backends() -> Result<()> {
+    use prover::programs::config::CompilerBackend;
+
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, config, ink) = test_configs();
+    let args = [&[0x03][..], b"backends"].concat();
+
+    let mut optimized = compile.clone();
+    optimized.debug.backend = CompilerBackend::Cranelift;
+
+    let run = |compile: &CompileConfig| -> Result<(Vec<u8>, u64)> {
+        let mut native = TestInstance::new_linked(filename, compile, config)?;
+        let output = run_native(&mut native, &args, ink)?;
+        Ok((output, native.ink_left().ink()))
+    };
+    assert_eq!(run(&compile)?, run(&optimized)?);
+
+    // consensus never compiles with anything but singlepass
+    let mut onchain = CompileConfig::version(compile.version, false);
+    onchain.debug.backend = CompilerBackend::Cranelift;
+    assert_eq!(onchain.backend(), CompilerBackend::Singlepass);
+    assert_eq!(optimized.backend(), CompilerBackend::Cranelift);
+    Ok(())
+}
+
+#[test]
+fn test_skip_verifier() -> Result<()> {
+    let filename = "tests/erc20/target/wasm32-unknown-unknown/release/erc20.wasm";
+    let (compile, config, ink) = test_configs();
+    let wasm = std::fs::read(filename)?;
+
+    let mut trusted = compile.clone();
+    trusted.debug.skip_verifier = true;
+
+    let instantiate = |compile: &CompileConfig| -> Result<(Duration, TestInstance)> {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        let start = Instant::now();
+        let native = TestInstance::from_wasm(&wasm, evm, evm_data, compile, config)?;
+        Ok((start.elapsed(), native))
+    };
+
+    let (verified, mut checked) = instantiate(&compile)?;
+    let (skipped, mut unchecked) = instantiate(&trusted)?;
+    println!(
+        "Verified {} Skipped {}",
+        format::time(verified),
+        format::time(skipped)
+    );
+
+    // the verifier changes nothing about execution
+    let args = [0x00; 4];
+    let checked = checked.run_main(&args, config, ink)?;
+    let unchecked = unchecked.run_main(&args, config, ink)?;
+    assert_eq!(checked.into_data(), unchecked.into_data());
+    assert_ne!(compile.fingerprint(), trusted.fingerprint());
+    Ok(())
+}
+
+#[test]
+fn test_module_fingerprint() -> Result<()> {
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, config, ink) = test_configs();
+    let wasm = std::fs::read(filename)?;
+    let module = native::module(&wasm, compile.clone())?;
+
+    let deserialize = |compile: &CompileConfig| {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        unsafe { TestInstance::deserialize(&module, compile.clone(), evm, evm_data) }
+    };
+
+    let outcome = deserialize(&compile)?.run_main(&[0x01, 0xde, 0xad], config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+
+    // modules only deserialize under the config they were compiled with
+    let mut other_debug = CompileConfig::version(0, false);
+    other_debug.debug.count_ops = compile.debug.count_ops;
+    let mut other_version = CompileConfig::version(1, true);
+    other_version.debug.count_ops = compile.debug.count_ops;
+    let mut other_table = compile.clone();
+    other_table.pricing.cost_table = Some(CostTable::new([], 1));
+
+    for other in [other_debug, other_version, other_table] {
+        assert_ne!(other.fingerprint(), compile.fingerprint());
+        let error = deserialize(&other).unwrap_err();
+        assert!(error.to_string().contains("different config"));
+    }
+    assert!(deserialize(&compile)?
+        .run_main(&[0x01, 0xde, 0xad], config, ink)
+        .is_ok());
+
+    // modules lacking a fingerprint are rejected too
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    let result = unsafe { TestInstance::deserialize(&module[..16], compile, evm, evm_data) };
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_module_header() -> Result<()> {
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, config, ink) = test_configs();
+    let wasm = std::fs::read(filename)?;
+    let module = native::module(&wasm, compile.clone())?;
+
+    let deserialize = |module: &[u8]| {
+        let (evm, evm_data) = TestEvmApi::new(compile.clone());
+        unsafe { TestInstance::deserialize(module, compile.clone(), evm, evm_data) }
+    };
+    let mut native = deserialize(&module)?;
+    let outcome = native.run_main(&[0x01, 0xde, 0xad], config, ink)?;
+    assert_eq!(outcome.kind(), UserOutcomeKind::Success);
+
+    // the header is the magic, the length-prefixed wasmer version, then the fingerprint
+    let version = 4 + 1;
+    let fingerprint = version + wasmer::VERSION.len();
+
+    let corrupt = |index: usize, expected: &str| {
+        let mut module = module.clone();
+        module[index] ^= 0xff;
+        let error = deserialize(&module).unwrap_err();
+        assert!(error.to_string().contains(expected), "{error}");
+    };
+    corrupt(0, "not a serialized stylus module");
+    corrupt(version, "serialized by wasmer");
+    corrupt(fingerprint, "different config");
+
+    let error = deserialize(&module[..fingerprint]).unwrap_err();
+    assert!(error.to_string().contains("truncated"));
+    Ok(())
+}
+
+#[test]
+fn test_module_hash() -> Result<()> {
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, _, _) = test_configs();
+    let wasm = std::fs::read(filename)?;
+    let module = native::module(&wasm, compile.clone())?;
+
+    // the hash matches the key deserialization caches the module under
+    let hash = native::module_hash(&module)?;
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    unsafe { TestInstance::deserialize(&module, compile.clone(), evm, evm_data)? };
+    assert!(ModuleCache::get(hash, &compile).is_some());
+
+    assert!(native::module_hash(&module[1..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_reset() -> Result<()> {
+    // in keccak.rs
+    //     the input is the # of hashings followed by a preimage
+    //     the output is the iterated hash of the preimage
+
+    let filename = "tests/keccak/target/wasm32-unknown-unknown/release/keccak.wasm";
+    let (compile, config, ink) = test_configs();
+
+    let first = vec![0x04, 0xde, 0xad, 0xbe, 0xef];
+    let second = vec![0x01, 0x01];
+
+    let mut fresh = TestInstance::new_linked(filename, &compile, config)?;
+    let expected = run_native(&mut fresh, &second, ink)?;
+
+    let mut pooled = TestInstance::new_linked(filename, &compile, config)?;
+    pooled
+        .reset(config, fresh.env().evm_api.clone(), fresh.env().evm_data)
+        .unwrap_err(); // no snapshot yet
+
+    pooled.snapshot()?;
+    run_native(&mut pooled, &first, ink)?;
+
+    let (evm, evm_data) = TestEvmApi::new(compile.clone());
+    pooled.reset(config, evm, evm_data)?;
+    assert!(pooled.env().args.is_empty());
+    assert!(pooled.env().outs.is_empty());
+
+    let output = run_native(&mut pooled, &second, ink)?;
+    assert_eq!(hex::encode(output), hex::encode(expected));
+    assert_eq!(pooled.ink_left(), fresh.ink_left());
+    Ok(())
+}
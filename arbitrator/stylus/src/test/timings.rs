@@ -20,8 +20,8 @@ fn test_timings() -> Result<()> {
         "evm_gas_left", "evm_ink_left",
 
         // evm data
-        "block_basefee", "chainid", "block_coinbase", "block_number", "contract_address",
-        "msg_sender", "msg_value", "tx_gas_price", "tx_origin",
+        "block_basefee", "block_prevrandao", "block_blob_basefee", "chainid", "block_coinbase",
+        "block_number", "contract_address", "msg_sender", "msg_value", "tx_gas_price", "tx_origin",
     ];
 
     let loops = ["read_args", "write_result", "keccak"];
@@ -0,0 +1,9 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory (export "memory") 1 1)
+    (data $passive "\01\02\03\04")
+    (func (export "init")
+        (memory.init $passive (i32.const 0) (i32.const 0) (i32.const 4))
+        (data.drop $passive)))
@@ -3,6 +3,7 @@
 
 (module
     (import "console" "log_txt" (func $log_txt (param i32 i32)))
+    (import "console" "log_bytes" (func $log_bytes (param i32 i32)))
     (import "console" "log_i32" (func $log_i32 (param i32)))
     (import "console" "log_i64" (func $log_i64 (param i64)))
     (import "console" "log_f32" (func $log_f32 (param f32)))
@@ -15,6 +16,7 @@
     (data (i32.const 0xa4b) "\57\65\20\68\61\76\65\20\74\68\65\20\69\6E\6B\21") ;; We have the ink!
     (func $start
         (call $log_txt (i32.const 0xa4b) (i32.const 16))
+        (call $log_bytes (i32.const 0xa4b) (i32.const 16))
 
         i32.const 48
         call $tee_i32
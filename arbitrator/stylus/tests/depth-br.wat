@@ -0,0 +1,17 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/nitro/blob/master/LICENSE
+
+(module
+    (memory 0 0)
+    (export "memory" (memory 0))
+    (func (export "br_out") (result i32)
+        (block
+            i32.const 7
+            br 1)       ;; exits the function from within a block
+        i32.const 0)
+    (func (export "br_if_out") (param $exit i32) (result i32)
+        i32.const 1
+        local.get $exit
+        br_if 0         ;; exits the function when $exit is nonzero
+        drop
+        i32.const 2))
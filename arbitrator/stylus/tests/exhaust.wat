@@ -0,0 +1,14 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory 1)
+    (export "memory" (memory 0))
+    (func (export "copy") (param $len i32)
+        (memory.copy (i32.const 0) (i32.const 0) (local.get $len)))
+    (func (export "fill") (param $len i32)
+        (memory.fill (i32.const 0) (i32.const 0) (local.get $len)))
+    (func (export "grow") (param $pages i32)
+        (drop (memory.grow (local.get $pages))))
+    (func (export "grow_one") (param i32)
+        (drop (memory.grow (i32.const 1)))))
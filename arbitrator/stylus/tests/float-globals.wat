@@ -0,0 +1,7 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory (export "memory") 0 0)
+    (global (export "single") (mut f32) (f32.const 1.5))
+    (global (export "double") (mut f64) (f64.const -2.25)))
@@ -0,0 +1,9 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory 0 0)
+    (export "memory" (memory 0))
+    (func (export "half") (param $value i32) (result i32)
+        (i32.trunc_f32_s
+            (f32.div (f32.convert_i32_s (local.get $value)) (f32.const 2)))))
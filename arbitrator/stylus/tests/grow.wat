@@ -0,0 +1,10 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory 0 0)
+    (export "memory" (memory 0))
+    (func (export "grow") (param $pages i32) (result i32)
+        (memory.grow (local.get $pages)))
+    (func (export "grow_three") (result i32)
+        (memory.grow (i32.const 3))))
@@ -0,0 +1,14 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory 0 0)
+    (export "memory" (memory 0))
+    (func (export "spin") (param $count i32) (result i32)
+        (local $sum i32)
+        (loop $loop
+            (local.set $sum (i32.add (local.get $sum) (i32.const 1)))
+            (local.set $count (i32.sub (local.get $count) (i32.const 1)))
+            (br_if $loop (local.get $count))
+        )
+        local.get $sum))
@@ -0,0 +1,7 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (memory (export "memory") 0 0)
+    (func $null (export "null") (result i32)
+        (ref.is_null (ref.func $null))))
@@ -0,0 +1,15 @@
+;; Copyright 2023, Offchain Labs, Inc.
+;; For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
+
+(module
+    (global $status (export "status") (mut i32) (i32.const 10))
+    (memory (export "memory") 0 0)
+    (func $start
+        global.get $status
+        i32.const 1
+        i32.add
+        global.set $status)
+    (func (export "stylus_start")
+        i32.const 100
+        global.set $status)
+    (start $start))
@@ -3,30 +3,60 @@
 
 use crate::{evm_api::ApiCaller, Program};
 use arbutil::{
-    crypto,
+    blake2f, bn254, crypto,
     evm::{self, api::EvmApi, js::JsEvmApi, user::UserOutcomeKind},
-    pricing::{EVM_API_INK, HOSTIO_INK, PTR_INK},
+    pricing::{HostioKind, HOSTIO_INK},
     wavm, Bytes20, Bytes32,
 };
 use prover::programs::meter::{GasMeteredMachine, MeteredMachine};
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__read_args(ptr: usize) {
-    let program = Program::start(0);
-    program.pay_for_write(program.args.len() as u64).unwrap();
+    let program = Program::start(HostioKind::ReadArgs);
+    let len = program.args.len() as u32;
+    program.pay_for_memory_expansion(ptr as u32, len).unwrap();
+    program.pay_for_write(len.into()).unwrap();
     wavm::write_slice_usize(&program.args, ptr);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__read_args_slice(ptr: usize, offset: u32, len: u32) {
+    let program = Program::start(HostioKind::ReadArgsSlice);
+    let end = offset.checked_add(len);
+    if end.map_or(true, |end| end as usize > program.args.len()) {
+        program.logical("args slice out of range");
+    }
+    program.pay_for_evm_copy_into(ptr as u32, len).unwrap();
+
+    let (offset, len) = (offset as usize, len as usize);
+    wavm::write_slice_usize(&program.args[offset..offset + len], ptr);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__args_len() -> u32 {
+    let program = Program::start(HostioKind::ArgsLen);
+    program.args.len() as u32
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__write_result(ptr: usize, len: usize) {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::WriteResult);
+    program.pay_for_read(len as u64).unwrap();
+    program.outs = wavm::read_slice_usize(ptr, len);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__abort(ptr: usize, len: usize) {
+    let program = Program::start(HostioKind::Abort);
     program.pay_for_read(len as u64).unwrap();
     program.outs = wavm::read_slice_usize(ptr, len);
+    program.escaped = Some(UserOutcomeKind::Revert);
+    panic!("program aborted");
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__storage_load_bytes32(key: usize, dest: usize) {
-    let program = Program::start(2 * PTR_INK + EVM_API_INK);
+    let program = Program::start(HostioKind::StorageLoadBytes32);
     let key = wavm::read_bytes32(key);
 
     let (value, gas_cost) = program.evm_api.get_bytes32(key);
@@ -36,7 +66,7 @@ pub unsafe extern "C" fn user_host__storage_load_bytes32(key: usize, dest: usize
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__storage_store_bytes32(key: usize, value: usize) {
-    let program = Program::start(2 * PTR_INK + EVM_API_INK);
+    let program = Program::start(HostioKind::StorageStoreBytes32);
     program.require_gas(evm::SSTORE_SENTRY_GAS).unwrap();
 
     let api = &mut program.evm_api;
@@ -62,7 +92,17 @@ pub unsafe extern "C" fn user_host__call_contract(
     let call = |api: EvmCaller, contract, input, gas, value: Option<_>| {
         api.contract_call(contract, input, gas, value.unwrap())
     };
-    do_call(contract, calldata, calldata_len, value, gas, ret_len, call)
+    let kind = HostioKind::CallContract;
+    do_call(
+        contract,
+        calldata,
+        calldata_len,
+        value,
+        gas,
+        ret_len,
+        kind,
+        call,
+    )
 }
 
 #[no_mangle]
@@ -74,7 +114,17 @@ pub unsafe extern "C" fn user_host__delegate_call_contract(
     ret_len: usize,
 ) -> u8 {
     let call = |api: EvmCaller, contract, input, gas, _| api.delegate_call(contract, input, gas);
-    do_call(contract, calldata, calldata_len, None, gas, ret_len, call)
+    let kind = HostioKind::DelegateCallContract;
+    do_call(
+        contract,
+        calldata,
+        calldata_len,
+        None,
+        gas,
+        ret_len,
+        kind,
+        call,
+    )
 }
 
 #[no_mangle]
@@ -86,7 +136,17 @@ pub unsafe extern "C" fn user_host__static_call_contract(
     ret_len: usize,
 ) -> u8 {
     let call = |api: EvmCaller, contract, input, gas, _| api.static_call(contract, input, gas);
-    do_call(contract, calldata, calldata_len, None, gas, ret_len, call)
+    let kind = HostioKind::StaticCallContract;
+    do_call(
+        contract,
+        calldata,
+        calldata_len,
+        None,
+        gas,
+        ret_len,
+        kind,
+        call,
+    )
 }
 
 unsafe fn do_call<F>(
@@ -96,12 +156,13 @@ unsafe fn do_call<F>(
     value: Option<usize>,
     mut gas: u64,
     return_data_len: usize,
+    kind: HostioKind,
     call: F,
 ) -> u8
 where
     F: FnOnce(EvmCaller, Bytes20, Vec<u8>, u64, Option<Bytes32>) -> (u32, u64, UserOutcomeKind),
 {
-    let program = Program::start(3 * PTR_INK + EVM_API_INK);
+    let program = Program::start(kind);
     program.pay_for_read(calldata_len as u64).unwrap();
     gas = gas.min(program.gas_left().unwrap());
 
@@ -125,7 +186,7 @@ pub unsafe extern "C" fn user_host__create1(
     contract: usize,
     revert_data_len: usize,
 ) {
-    let program = Program::start(3 * PTR_INK + EVM_API_INK);
+    let program = Program::start(HostioKind::Create1);
     program.pay_for_read(code_len as u64).unwrap();
 
     let code = wavm::read_slice_usize(code, code_len);
@@ -149,7 +210,7 @@ pub unsafe extern "C" fn user_host__create2(
     contract: usize,
     revert_data_len: usize,
 ) {
-    let program = Program::start(4 * PTR_INK + EVM_API_INK);
+    let program = Program::start(HostioKind::Create2);
     program.pay_for_read(code_len as u64).unwrap();
 
     let code = wavm::read_slice_usize(code, code_len);
@@ -165,30 +226,60 @@ pub unsafe extern "C" fn user_host__create2(
     wavm::write_bytes20(contract, result.unwrap());
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__create2_address(code_hash: usize, salt: usize, dest: usize) {
+    let program = Program::start(HostioKind::Create2Address);
+    program.pay_for_keccak(85).unwrap();
+
+    let code_hash = wavm::read_bytes32(code_hash);
+    let salt = wavm::read_bytes32(salt);
+    let address = evm::create2_address(program.evm_data.contract_address, salt, code_hash);
+    wavm::write_bytes20(dest, address);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__read_return_data(
     ptr: usize,
     offset: usize,
     size: usize,
 ) -> usize {
-    let program = Program::start(EVM_API_INK);
-    program.pay_for_write(size as u64).unwrap();
+    let program = Program::start(HostioKind::ReadReturnData);
+    let (offset, size) = (offset as u32, size as u32);
+    program.pay_for_memory_expansion(ptr as u32, size).unwrap();
+    program.pay_for_write(size.into()).unwrap();
 
-    let data = program.evm_api.get_return_data(offset as u32, size as u32);
-    assert!(data.len() <= size);
+    let data = program.evm_api.get_return_data(offset, size);
+    assert!(data.len() <= size as usize);
     wavm::write_slice_usize(&data, ptr);
     data.len()
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__read_return_data_slice(ptr: usize, offset: u32, size: u32) {
+    let program = Program::start(HostioKind::ReadReturnDataSlice);
+    let end = offset.checked_add(size);
+    if end.map_or(true, |end| end > program.evm_data.return_data_len) {
+        program.logical("return data slice out of range");
+    }
+    program.pay_for_evm_copy_into(ptr as u32, size).unwrap();
+    program.pay_for_write(size.into()).unwrap();
+
+    let data = program.evm_api.get_return_data(offset, size);
+    if data.len() != size as usize {
+        program.logical("return data shorter than requested");
+    }
+    wavm::write_slice_usize(&data, ptr);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__return_data_size() -> u32 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::ReturnDataSize);
     program.evm_data.return_data_len
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__emit_log(data: usize, len: u32, topics: u32) {
-    let program = Program::start(EVM_API_INK);
+    let program = Program::start(HostioKind::EmitLog);
     if topics > 4 || len < topics * 32 {
         panic!("bad topic data");
     }
@@ -199,9 +290,29 @@ pub unsafe extern "C" fn user_host__emit_log(data: usize, len: u32, topics: u32)
     program.evm_api.emit_log(data, topics).unwrap();
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__emit_log_separate(
+    topics: usize,
+    count: u32,
+    data: usize,
+    len: u32,
+) {
+    let program = Program::start(HostioKind::EmitLogSeparate);
+    if count > 4 {
+        panic!("bad topic data");
+    }
+    program.pay_for_read((count * 32).into()).unwrap();
+    program.pay_for_read(len.into()).unwrap();
+    program.pay_for_evm_log(count, len).unwrap();
+
+    let mut log = wavm::read_slice_usize(topics, count as usize * 32);
+    log.extend(wavm::read_slice_usize(data, len as usize));
+    program.evm_api.emit_log(log, count).unwrap();
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__account_balance(address: usize, ptr: usize) {
-    let program = Program::start(2 * PTR_INK + EVM_API_INK);
+    let program = Program::start(HostioKind::AccountBalance);
     let address = wavm::read_bytes20(address);
 
     let (value, gas_cost) = program.evm_api.account_balance(address);
@@ -211,7 +322,7 @@ pub unsafe extern "C" fn user_host__account_balance(address: usize, ptr: usize)
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__account_codehash(address: usize, ptr: usize) {
-    let program = Program::start(2 * PTR_INK + EVM_API_INK);
+    let program = Program::start(HostioKind::AccountCodehash);
     let address = wavm::read_bytes20(address);
 
     let (value, gas_cost) = program.evm_api.account_codehash(address);
@@ -219,81 +330,125 @@ pub unsafe extern "C" fn user_host__account_codehash(address: usize, ptr: usize)
     wavm::write_bytes32(ptr, value);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__account_storage_root(address: usize, ptr: usize) {
+    let program = Program::start(HostioKind::AccountStorageRoot);
+    let address = wavm::read_bytes20(address);
+
+    let (value, gas_cost) = program.evm_api.account_storage_root(address);
+    program.buy_gas(gas_cost).unwrap();
+    wavm::write_bytes32(ptr, value);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__account_nonce(address: usize) -> u64 {
+    let program = Program::start(HostioKind::AccountNonce);
+    let address = wavm::read_bytes20(address);
+
+    let (nonce, gas_cost) = program.evm_api.account_nonce(address);
+    program.buy_gas(gas_cost).unwrap();
+    nonce
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__selfdestruct(beneficiary: usize) {
+    let program = Program::start(HostioKind::SelfDestruct);
+    let beneficiary = wavm::read_bytes20(beneficiary);
+
+    let gas_cost = program.evm_api.selfdestruct(beneficiary).unwrap();
+    program.buy_gas(gas_cost).unwrap();
+    program.outs.clear();
+    program.escaped = Some(UserOutcomeKind::Success);
+    panic!("program self-destructed");
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__evm_gas_left() -> u64 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::EvmGasLeft);
     program.gas_left().unwrap()
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__evm_ink_left() -> u64 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::EvmInkLeft);
     program.ink_ready().unwrap()
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_basefee(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::BlockBasefee);
     wavm::write_bytes32(ptr, program.evm_data.block_basefee)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__block_prevrandao(ptr: usize) {
+    let program = Program::start(HostioKind::BlockPrevrandao);
+    wavm::write_bytes32(ptr, program.evm_data.block_prevrandao)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__block_blob_basefee(ptr: usize) {
+    let program = Program::start(HostioKind::BlockBlobBasefee);
+    wavm::write_bytes32(ptr, program.evm_data.block_blob_basefee)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__chainid() -> u64 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::Chainid);
     program.evm_data.chainid
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_coinbase(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::BlockCoinbase);
     wavm::write_bytes20(ptr, program.evm_data.block_coinbase)
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_gas_limit() -> u64 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::BlockGasLimit);
     program.evm_data.block_gas_limit
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_number() -> u64 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::BlockNumber);
     program.evm_data.block_number
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__block_timestamp() -> u64 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::BlockTimestamp);
     program.evm_data.block_timestamp
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__contract_address(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::ContractAddress);
     wavm::write_bytes20(ptr, program.evm_data.contract_address)
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__msg_reentrant() -> u32 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::MsgReentrant);
     program.evm_data.reentrant
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__msg_sender(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::MsgSender);
     wavm::write_bytes20(ptr, program.evm_data.msg_sender)
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__msg_value(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::MsgValue);
     wavm::write_bytes32(ptr, program.evm_data.msg_value)
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__native_keccak256(bytes: usize, len: usize, output: usize) {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::NativeKeccak256);
     program.pay_for_keccak(len as u64).unwrap();
 
     let preimage = wavm::read_slice_usize(bytes, len);
@@ -301,30 +456,107 @@ pub unsafe extern "C" fn user_host__native_keccak256(bytes: usize, len: usize, o
     wavm::write_bytes32(output, digest.into())
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__lib_bn254_add(input: usize, output: usize) -> u8 {
+    let program = Program::start(HostioKind::Bn254Add);
+    program.buy_gas(evm::BN254_ADD_GAS).unwrap();
+
+    let input = wavm::read_slice_usize(input, bn254::ADD_INPUT_LEN);
+    let Some(point) = bn254::add(&input.try_into().unwrap()) else {
+        return 1;
+    };
+    wavm::write_slice_usize(&point, output);
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__lib_bn254_mul(input: usize, output: usize) -> u8 {
+    let program = Program::start(HostioKind::Bn254Mul);
+    program.buy_gas(evm::BN254_MUL_GAS).unwrap();
+
+    let input = wavm::read_slice_usize(input, bn254::MUL_INPUT_LEN);
+    let Some(point) = bn254::mul(&input.try_into().unwrap()) else {
+        return 1;
+    };
+    wavm::write_slice_usize(&point, output);
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__lib_bn254_pairing(
+    input: usize,
+    len: usize,
+    output: usize,
+) -> u8 {
+    let program = Program::start(HostioKind::Bn254Pairing);
+    program.pay_for_read(len as u64).unwrap();
+    let pairs = (len / bn254::PAIR_LEN) as u64;
+    let gas_cost = evm::BN254_PAIRING_BASE_GAS + pairs * evm::BN254_PAIRING_POINT_GAS;
+    program.buy_gas(gas_cost).unwrap();
+
+    let input = wavm::read_slice_usize(input, len);
+    let Some(success) = bn254::pairing(&input) else {
+        return 1;
+    };
+    let mut result = Bytes32::default();
+    result[31] = success.into();
+    wavm::write_bytes32(output, result);
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn user_host__lib_blake2f(input: usize, len: usize, output: usize) -> u8 {
+    let program = Program::start(HostioKind::Blake2f);
+    if len != blake2f::INPUT_LEN {
+        return 1;
+    }
+    let input = wavm::read_slice_usize(input, len).try_into().unwrap();
+    program.buy_gas(blake2f::rounds(&input).into()).unwrap();
+
+    let Some(state) = blake2f::compress(&input) else {
+        return 1;
+    };
+    wavm::write_slice_usize(&state, output);
+    0
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__tx_gas_price(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::TxGasPrice);
     wavm::write_bytes32(ptr, program.evm_data.tx_gas_price)
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__tx_ink_price() -> u32 {
-    let program = Program::start(0);
+    let program = Program::start(HostioKind::TxInkPrice);
     program.pricing().ink_price
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn user_host__tx_origin(ptr: usize) {
-    let program = Program::start(PTR_INK);
+    let program = Program::start(HostioKind::TxOrigin);
     wavm::write_bytes20(ptr, program.evm_data.tx_origin)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn user_host__tx_type() -> u32 {
+    let program = Program::start(HostioKind::TxType);
+    program.evm_data.tx_type.into()
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn user_host__memory_grow(pages: u16) {
     let program = Program::start_free();
     if pages == 0 {
         return program.buy_ink(HOSTIO_INK).unwrap();
     }
+    if let Some(bound) = program.grow_bound {
+        let open = program.memory_size();
+        if open.saturating_add(pages.into()) > bound {
+            program.escaped = Some(UserOutcomeKind::OutOfMemory);
+            panic!("program exceeded its grow bound");
+        }
+    }
     let gas_cost = program.evm_api.add_pages(pages);
     program.buy_gas(gas_cost).unwrap();
 }
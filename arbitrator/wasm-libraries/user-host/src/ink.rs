@@ -2,8 +2,10 @@
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
 use crate::Program;
+use arbutil::evm;
 use prover::programs::{
     config::PricingParams,
+    meter::OutOfInkError,
     prelude::{GasMeteredMachine, MachineMeter, MeteredMachine},
 };
 
@@ -12,6 +14,7 @@ extern "C" {
     fn user_ink_left() -> u64;
     fn user_ink_status() -> u32;
     fn user_set_ink(ink: u64, status: u32);
+    fn user_memory_size() -> u32;
 }
 
 impl MeteredMachine for Program {
@@ -36,3 +39,24 @@ impl GasMeteredMachine for Program {
         self.config.pricing
     }
 }
+
+impl Program {
+    /// The pages of memory the program currently has open.
+    pub fn memory_size(&self) -> u32 {
+        unsafe { user_memory_size() }
+    }
+
+    /// Pays for an EVM-style copy of `len` bytes to `dest`, including any memory expansion.
+    pub fn pay_for_evm_copy_into(&mut self, dest: u32, len: u32) -> Result<(), OutOfInkError> {
+        self.pay_for_evm_copy(len.into())?;
+        self.pay_for_memory_expansion(dest, len)
+    }
+
+    /// Pays for growing the memory touched by EVM-style copies to cover `len` bytes at `dest`.
+    pub fn pay_for_memory_expansion(&mut self, dest: u32, len: u32) -> Result<(), OutOfInkError> {
+        let (words, cost) = evm::memory_expansion(self.memory_words, dest, len);
+        self.buy_gas(cost)?;
+        self.memory_words = words;
+        Ok(())
+    }
+}
@@ -2,11 +2,14 @@
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
 use arbutil::{
-    evm::{js::JsEvmApi, EvmData},
-    pricing,
+    evm::{js::JsEvmApi, user::UserOutcomeKind, EvmData},
+    pricing::HostioKind,
 };
 use evm_api::ApiCaller;
-use prover::programs::{meter::MeteredMachine, prelude::StylusConfig};
+use prover::programs::{
+    meter::MeteredMachine,
+    prelude::{CompileConfig, StylusConfig},
+};
 
 mod evm_api;
 mod host;
@@ -21,6 +24,11 @@ pub(crate) struct Program {
     evm_api: JsEvmApi<ApiCaller>,
     evm_data: EvmData,
     config: StylusConfig,
+    memory_words: u64,
+    /// The most pages the program may grow its memory to, if bounded
+    grow_bound: Option<u32>,
+    /// The outcome a hostio forced upon trapping, such as a revert via `abort`
+    escaped: Option<UserOutcomeKind>,
 }
 
 impl Program {
@@ -30,12 +38,16 @@ impl Program {
         evm_data: EvmData,
         config: StylusConfig,
     ) -> Self {
+        let bounds = CompileConfig::version(config.version, false).bounds;
         Self {
             args,
             outs: vec![],
             evm_api,
             evm_data,
             config,
+            memory_words: 0,
+            grow_bound: bounds.grow_bound.map(|pages| pages.0),
+            escaped: None,
         }
     }
 
@@ -43,13 +55,20 @@ impl Program {
         self.outs
     }
 
-    pub fn start(cost: u64) -> &'static mut Self {
+    pub fn start(kind: HostioKind) -> &'static mut Self {
         let program = Self::start_free();
-        program.buy_ink(pricing::HOSTIO_INK + cost).unwrap();
+        program.buy_ink(kind.ink()).unwrap();
         program
     }
 
     pub fn start_free() -> &'static mut Self {
         unsafe { PROGRAMS.last_mut().expect("no program") }
     }
+
+    /// Fails the call, mirroring `Escape::logical` in native execution
+    pub fn logical(&mut self, error: &'static str) -> ! {
+        self.outs = error.as_bytes().to_vec();
+        self.escaped = Some(UserOutcomeKind::Failure);
+        panic!("{error}");
+    }
 }
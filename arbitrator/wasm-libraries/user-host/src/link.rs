@@ -99,10 +99,28 @@ pub unsafe extern "C" fn go__github_com_offchainlabs_nitro_arbos_programs_callUs
     // buy ink
     let pricing = config.pricing;
     let gas = sp.read_go_ptr();
-    let ink = pricing.gas_to_ink(wavm::caller_load64(gas));
+    let ink = pricing.try_gas_to_ink(wavm::caller_load64(gas));
 
     // compute the module root, or accept one from the caller
     let root = sp.read_go_ptr();
+
+    // take all gas when the conversion overflows
+    let ink = match ink {
+        Ok(ink) => ink,
+        Err(error) => {
+            let error = error.wrap_err("call failed").debug_bytes();
+            sp.write_u8(UserOutcomeKind::Failure as u8).skip_space();
+            sp.write_ptr(heapify(error));
+            wavm::caller_store64(gas, 0);
+            return;
+        }
+    };
+    if let Err(error) = evm_data.validate() {
+        let error = error.wrap_err("call failed").debug_bytes();
+        sp.write_u8(UserOutcomeKind::Failure as u8).skip_space();
+        sp.write_ptr(heapify(error));
+        return;
+    }
     let root = (root != 0).then(|| wavm::read_bytes32(root));
     let module = root.unwrap_or_else(|| machine.main_module_hash());
     let (main, internals) = machine.program_info();
@@ -118,8 +136,12 @@ pub unsafe extern "C" fn go__github_com_offchainlabs_nitro_arbos_programs_callUs
 
     // call the program
     let go_stack = sp.save_stack();
-    let status = program_call_main(module, main, args_len);
-    let outs = PROGRAMS.pop().unwrap().into_outs();
+    let mut status = program_call_main(module, main, args_len);
+    let program = PROGRAMS.pop().unwrap();
+    if let Some(kind) = program.escaped {
+        status = kind as u32;
+    }
+    let outs = program.into_outs();
     sp.restore_stack(go_stack);
 
     /// cleans up and writes the output
@@ -240,14 +262,16 @@ pub unsafe extern "C" fn go__github_com_offchainlabs_nitro_arbos_programs_rustCo
 ///
 /// The Go compiler expects the call to take the form
 ///     λ(
-///         blockBasefee *[32]byte, chainid u64, blockCoinbase *[20]byte, blockGasLimit,
-///         blockNumber, blockTimestamp u64, contractAddress, msgSender *[20]byte,
-///         msgValue, txGasPrice *[32]byte, txOrigin *[20]byte, reentrant u32,
+///         blockBasefee, blockPrevrandao, blockBlobBasefee *[32]byte, chainid u64,
+///         blockCoinbase *[20]byte, blockGasLimit, blockNumber, blockTimestamp u64,
+///         contractAddress, msgSender *[20]byte, msgValue, txGasPrice *[32]byte,
+///         txOrigin *[20]byte, reentrant u32, txType u8,
 ///     ) -> *EvmData
 ///
 /// These values are placed on the stack as follows
-///     || baseFee || chainid || coinbase || gas limit || block number || timestamp || address ||
-///     || sender || value || gas price || origin || reentrant | 4 pad || data ptr ||
+///     || baseFee || prevrandao || blob baseFee || chainid || coinbase || gas limit ||
+///     || block number || timestamp || address || sender || value || gas price || origin ||
+///     || reentrant | tx type | 3 pad || data ptr ||
 ///
 #[no_mangle]
 pub unsafe extern "C" fn go__github_com_offchainlabs_nitro_arbos_programs_rustEvmDataImpl(
@@ -255,21 +279,23 @@ pub unsafe extern "C" fn go__github_com_offchainlabs_nitro_arbos_programs_rustEv
 ) {
     use wavm::{read_bytes20, read_bytes32};
     let mut sp = GoStack::new(sp);
-    let evm_data = EvmData {
-        block_basefee: read_bytes32(sp.read_go_ptr()),
-        chainid: sp.read_u64(),
-        block_coinbase: read_bytes20(sp.read_go_ptr()),
-        block_gas_limit: sp.read_u64(),
-        block_number: sp.read_u64(),
-        block_timestamp: sp.read_u64(),
-        contract_address: read_bytes20(sp.read_go_ptr()),
-        msg_sender: read_bytes20(sp.read_go_ptr()),
-        msg_value: read_bytes32(sp.read_go_ptr()),
-        tx_gas_price: read_bytes32(sp.read_go_ptr()),
-        tx_origin: read_bytes20(sp.read_go_ptr()),
-        reentrant: sp.read_u32(),
-        return_data_len: 0,
-    };
+    let evm_data = EvmData::new_unchecked(
+        read_bytes32(sp.read_go_ptr()),
+        read_bytes32(sp.read_go_ptr()),
+        read_bytes32(sp.read_go_ptr()),
+        sp.read_u64(),
+        read_bytes20(sp.read_go_ptr()),
+        sp.read_u64(),
+        sp.read_u64(),
+        sp.read_u64(),
+        read_bytes20(sp.read_go_ptr()),
+        read_bytes20(sp.read_go_ptr()),
+        read_bytes32(sp.read_go_ptr()),
+        read_bytes32(sp.read_go_ptr()),
+        read_bytes20(sp.read_go_ptr()),
+        sp.read_u32(),
+        sp.read_u8(),
+    );
     sp.skip_space();
     sp.write_ptr(heapify(evm_data));
 }
@@ -3,10 +3,10 @@
 
 #![allow(clippy::missing_safety_doc)]
 
-use crate::{Program, ARGS, EVER_PAGES, KEYS, LOGS, OPEN_PAGES, OUTS};
+use crate::{Program, ARGS, EVER_PAGES, KEYS, LOGS, OPEN_PAGES, ORIGINALS, OUTS, WARM};
 use arbutil::{
     crypto, evm,
-    pricing::{EVM_API_INK, HOSTIO_INK, PTR_INK},
+    pricing::{HostioKind, HOSTIO_INK},
     wavm,
 };
 use prover::programs::{
@@ -16,42 +16,70 @@ use prover::programs::{
 
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__read_args(ptr: usize) {
-    let mut program = Program::start(0);
-    program.pay_for_write(ARGS.len() as u64).unwrap();
+    let mut program = Program::start(HostioKind::ReadArgs);
+    let len = ARGS.len() as u32;
+    program.pay_for_memory_expansion(ptr as u32, len).unwrap();
+    program.pay_for_write(len.into()).unwrap();
     wavm::write_slice_usize(&ARGS, ptr);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn vm_hooks__read_args_slice(ptr: usize, offset: u32, len: u32) {
+    let mut program = Program::start(HostioKind::ReadArgsSlice);
+    let end = offset.checked_add(len);
+    if end.map_or(true, |end| end as usize > ARGS.len()) {
+        panic!("args slice out of range");
+    }
+    program.pay_for_evm_copy_into(ptr as u32, len).unwrap();
+
+    let (offset, len) = (offset as usize, len as usize);
+    wavm::write_slice_usize(&ARGS[offset..offset + len], ptr);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn vm_hooks__args_len() -> u32 {
+    let _ = Program::start(HostioKind::ArgsLen);
+    ARGS.len() as u32
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__write_result(ptr: usize, len: usize) {
-    let mut program = Program::start(0);
+    let mut program = Program::start(HostioKind::WriteResult);
     program.pay_for_read(len as u64).unwrap();
     OUTS = wavm::read_slice_usize(ptr, len);
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__storage_load_bytes32(key: usize, dest: usize) {
-    let mut program = Program::start(2 * PTR_INK + EVM_API_INK);
+    let mut program = Program::start(HostioKind::StorageLoadBytes32);
     let key = wavm::read_bytes32(key);
 
     let value = KEYS.lock().get(&key).cloned().unwrap_or_default();
-    program.buy_gas(2100).unwrap(); // pretend it was cold
+    match WARM.lock().insert(key) {
+        true => program.buy_gas(evm::COLD_SLOAD_GAS).unwrap(),
+        false => program.buy_gas(evm::WARM_ACCESS_GAS).unwrap(),
+    }
     wavm::write_bytes32(dest, value);
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__storage_store_bytes32(key: usize, value: usize) {
-    let mut program = Program::start(2 * PTR_INK + EVM_API_INK);
+    let mut program = Program::start(HostioKind::StorageStoreBytes32);
     program.require_gas(evm::SSTORE_SENTRY_GAS).unwrap();
-    program.buy_gas(22100).unwrap(); // pretend the worst case
 
     let key = wavm::read_bytes32(key);
     let value = wavm::read_bytes32(value);
-    KEYS.lock().insert(key, value);
+    let current = KEYS.lock().insert(key, value).unwrap_or_default();
+    let cold = WARM.lock().insert(key);
+
+    let original = *ORIGINALS.lock().entry(key).or_insert(current);
+    let (cost, _) = evm::sstore_cost(original, current, value, cold);
+    program.buy_gas(cost).unwrap();
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__emit_log(data: usize, len: u32, topics: u32) {
-    let mut program = Program::start(EVM_API_INK);
+    let mut program = Program::start(HostioKind::EmitLog);
     if topics > 4 || len < topics * 32 {
         panic!("bad topic data");
     }
@@ -78,7 +106,7 @@ pub unsafe extern "C" fn vm_hooks__memory_grow(pages: u16) {
 
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__native_keccak256(bytes: usize, len: usize, output: usize) {
-    let mut program = Program::start(0);
+    let mut program = Program::start(HostioKind::NativeKeccak256);
     program.pay_for_keccak(len as u64).unwrap();
 
     let preimage = wavm::read_slice_usize(bytes, len);
@@ -88,6 +116,6 @@ pub unsafe extern "C" fn vm_hooks__native_keccak256(bytes: usize, len: usize, ou
 
 #[no_mangle]
 pub unsafe extern "C" fn vm_hooks__msg_reentrant() -> u32 {
-    let _ = Program::start(0);
+    let _ = Program::start(HostioKind::MsgReentrant);
     0
 }
@@ -1,13 +1,14 @@
 // Copyright 2022-2023, Offchain Labs, Inc.
 // For license information, see https://github.com/OffchainLabs/nitro/blob/master/LICENSE
 
-use arbutil::pricing;
+use arbutil::{evm, pricing::HostioKind};
 use prover::programs::{
     config::PricingParams,
+    meter::OutOfInkError,
     prelude::{GasMeteredMachine, MachineMeter, MeteredMachine},
 };
 
-use crate::{Program, CONFIG};
+use crate::{Program, CONFIG, MEMORY_WORDS};
 
 #[link(wasm_import_module = "hostio")]
 extern "C" {
@@ -40,13 +41,27 @@ impl GasMeteredMachine for Program {
 }
 
 impl Program {
-    pub fn start(cost: u64) -> Self {
+    pub fn start(kind: HostioKind) -> Self {
         let mut program = Self::start_free();
-        program.buy_ink(pricing::HOSTIO_INK + cost).unwrap();
+        program.buy_ink(kind.ink()).unwrap();
         program
     }
 
     pub fn start_free() -> Self {
         Self
     }
+
+    /// Pays for an EVM-style copy of `len` bytes to `dest`, including any memory expansion.
+    pub fn pay_for_evm_copy_into(&mut self, dest: u32, len: u32) -> Result<(), OutOfInkError> {
+        self.pay_for_evm_copy(len.into())?;
+        self.pay_for_memory_expansion(dest, len)
+    }
+
+    /// Pays for growing the memory touched by EVM-style copies to cover `len` bytes at `dest`.
+    pub fn pay_for_memory_expansion(&mut self, dest: u32, len: u32) -> Result<(), OutOfInkError> {
+        let (words, cost) = evm::memory_expansion(unsafe { MEMORY_WORDS }, dest, len);
+        self.buy_gas(cost)?;
+        unsafe { MEMORY_WORDS = words };
+        Ok(())
+    }
 }
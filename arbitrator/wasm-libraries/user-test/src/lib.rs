@@ -4,7 +4,7 @@
 #![allow(clippy::missing_safety_doc)]
 
 use arbutil::Bytes32;
-use fnv::FnvHashMap as HashMap;
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use prover::programs::prelude::StylusConfig;
@@ -18,9 +18,12 @@ pub(crate) static mut LOGS: Vec<Vec<u8>> = vec![];
 pub(crate) static mut CONFIG: Option<StylusConfig> = None;
 pub(crate) static mut OPEN_PAGES: u16 = 0;
 pub(crate) static mut EVER_PAGES: u16 = 0;
+pub(crate) static mut MEMORY_WORDS: u64 = 0;
 
 lazy_static! {
     static ref KEYS: Mutex<HashMap<Bytes32, Bytes32>> = Mutex::new(HashMap::default());
+    static ref WARM: Mutex<HashSet<Bytes32>> = Mutex::new(HashSet::default());
+    static ref ORIGINALS: Mutex<HashMap<Bytes32, Bytes32>> = Mutex::new(HashMap::default());
 }
 
 /// Mock type representing a `user_host::Program`
@@ -36,6 +39,7 @@ pub unsafe extern "C" fn user_test__prepare(
     let config = StylusConfig::new(version, max_depth, ink_price);
     CONFIG = Some(config);
     ARGS = vec![0; len];
+    MEMORY_WORDS = 0;
     ARGS.as_ptr()
 }
 
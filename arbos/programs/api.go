@@ -44,6 +44,9 @@ type emitLogType func(data []byte, topics uint32) error
 type accountBalanceType func(address common.Address) (value common.Hash, cost uint64)
 type accountCodehashType func(address common.Address) (value common.Hash, cost uint64)
 type addPagesType func(pages uint16) (cost uint64)
+type selfDestructType func(beneficiary common.Address) (cost uint64, err error)
+type accountNonceType func(address common.Address) (nonce uint64, cost uint64)
+type accountStorageRootType func(address common.Address) (root common.Hash, cost uint64)
 
 type goClosures struct {
 	getBytes32      getBytes32Type
@@ -58,6 +61,9 @@ type goClosures struct {
 	accountBalance  accountBalanceType
 	accountCodeHash accountCodehashType
 	addPages        addPagesType
+	selfDestruct    selfDestructType
+	accountNonce    accountNonceType
+	storageRoot     accountStorageRootType
 }
 
 func newApiClosures(
@@ -269,6 +275,39 @@ func newApiClosures(
 		open, ever := db.AddStylusPages(pages)
 		return memoryModel.GasCost(pages, open, ever)
 	}
+	selfDestruct := func(beneficiary common.Address) (uint64, error) {
+		// Only the balance moves, as with EIP-6780 for contracts created in prior transactions.
+		// The gas matches gasSelfdestructEIP2929 in operations_acl.go.
+		if readOnly {
+			return 0, vm.ErrWriteProtection
+		}
+		cost := params.SelfdestructGasEIP150
+		if !db.AddressInAccessList(beneficiary) {
+			db.AddAddressToAccessList(beneficiary)
+			cost += params.ColdAccountAccessCostEIP2929
+		}
+		balance := db.GetBalance(actingAddress)
+		if balance.Sign() != 0 && db.Empty(beneficiary) {
+			cost += params.CreateBySelfdestructGas
+		}
+		db.SubBalance(actingAddress, balance)
+		db.AddBalance(beneficiary, balance)
+		return cost, nil
+	}
+	accountNonce := func(address common.Address) (uint64, uint64) {
+		cost := vm.WasmAccountTouchCost(evm.StateDB, address)
+		return evm.StateDB.GetNonce(address), cost
+	}
+	storageRoot := func(address common.Address) (common.Hash, uint64) {
+		// only state backed by a trie has a root, so other backends report the zero hash
+		cost := vm.WasmAccountTouchCost(evm.StateDB, address)
+		if rooted, ok := evm.StateDB.(interface {
+			GetStorageRoot(common.Address) common.Hash
+		}); ok {
+			return rooted.GetStorageRoot(address), cost
+		}
+		return common.Hash{}, cost
+	}
 
 	return &goClosures{
 		getBytes32:      getBytes32,
@@ -283,5 +322,8 @@ func newApiClosures(
 		accountBalance:  accountBalance,
 		accountCodeHash: accountCodehash,
 		addPages:        addPages,
+		selfDestruct:    selfDestruct,
+		accountNonce:    accountNonce,
+		storageRoot:     storageRoot,
 	}
 }
@@ -30,6 +30,7 @@ import (
 	"github.com/offchainlabs/nitro/arbos/burn"
 	"github.com/offchainlabs/nitro/arbos/util"
 	"github.com/offchainlabs/nitro/arbutil"
+	"github.com/offchainlabs/nitro/util/arbmath"
 )
 
 type u8 = C.uint8_t
@@ -83,7 +84,7 @@ func compileUserWasm(
 	status = userStatus(C.stylus_compile(
 		goSlice(wasm),
 		u16(version),
-		cbool(debug),
+		u32(arbmath.BoolToUint32(debug)),
 		output,
 	))
 	data, msg, err := status.toResult(output.intoBytes(), debug)
@@ -115,6 +116,8 @@ func callUserWasm(
 	defer dropApi(id)
 
 	output := &rustVec{}
+	opCounts := &rustVec{}
+	hostioGas := &rustVec{}
 	status := userStatus(C.stylus_call(
 		goSlice(module),
 		goSlice(calldata),
@@ -123,10 +126,18 @@ func callUserWasm(
 		evmData.encode(),
 		u32(stylusParams.debugMode),
 		output,
+		opCounts,
+		hostioGas,
 		(*u64)(&scope.Contract.Gas),
 	))
 
 	debug := stylusParams.debugMode != 0
+	if counts := opCounts.intoBytes(); debug && len(counts) > 0 {
+		log.Trace("program operator counts", "program", address, "counts", common.Bytes2Hex(counts))
+	}
+	if gas := hostioGas.intoBytes(); debug && len(gas) > 0 {
+		log.Trace("program hostio gas", "program", address, "gas", string(gas))
+	}
 	data, msg, err := status.toResult(output.intoBytes(), debug)
 	if status == userFailure && debug {
 		log.Warn("program failure", "err", err, "msg", msg, "program", address)
@@ -271,6 +282,35 @@ func addPagesImpl(api usize, pages u16) u64 {
 	return u64(cost)
 }
 
+//export accountNonceImpl
+func accountNonceImpl(api usize, address bytes20, cost *u64) u64 {
+	closures := getApi(api)
+	nonce, gas := closures.accountNonce(address.toAddress())
+	*cost = u64(gas)
+	return u64(nonce)
+}
+
+//export accountStorageRootImpl
+func accountStorageRootImpl(api usize, address bytes20, cost *u64) bytes32 {
+	closures := getApi(api)
+	root, gas := closures.storageRoot(address.toAddress())
+	*cost = u64(gas)
+	return hashToBytes32(root)
+}
+
+//export selfDestructImpl
+func selfDestructImpl(api usize, beneficiary bytes20, cost *u64, errVec *rustVec) apiStatus {
+	closures := getApi(api)
+
+	gas, err := closures.selfDestruct(beneficiary.toAddress())
+	if err != nil {
+		errVec.setString(err.Error())
+		return apiFailure
+	}
+	*cost = u64(gas)
+	return apiSuccess
+}
+
 func (value bytes20) toAddress() common.Address {
 	addr := common.Address{}
 	for index, b := range value.bytes {
@@ -349,19 +389,22 @@ func (params *goParams) encode() C.StylusConfig {
 
 func (data *evmData) encode() C.EvmData {
 	return C.EvmData{
-		block_basefee:    hashToBytes32(data.blockBasefee),
-		chainid:          u64(data.chainId),
-		block_coinbase:   addressToBytes20(data.blockCoinbase),
-		block_gas_limit:  u64(data.blockGasLimit),
-		block_number:     u64(data.blockNumber),
-		block_timestamp:  u64(data.blockTimestamp),
-		contract_address: addressToBytes20(data.contractAddress),
-		msg_sender:       addressToBytes20(data.msgSender),
-		msg_value:        hashToBytes32(data.msgValue),
-		tx_gas_price:     hashToBytes32(data.txGasPrice),
-		tx_origin:        addressToBytes20(data.txOrigin),
-		reentrant:        u32(data.reentrant),
-		return_data_len:  0,
+		block_basefee:      hashToBytes32(data.blockBasefee),
+		block_prevrandao:   hashToBytes32(data.blockPrevrandao),
+		block_blob_basefee: hashToBytes32(data.blockBlobBasefee),
+		chainid:            u64(data.chainId),
+		block_coinbase:     addressToBytes20(data.blockCoinbase),
+		block_gas_limit:    u64(data.blockGasLimit),
+		block_number:       u64(data.blockNumber),
+		block_timestamp:    u64(data.blockTimestamp),
+		contract_address:   addressToBytes20(data.contractAddress),
+		msg_sender:         addressToBytes20(data.msgSender),
+		msg_value:          hashToBytes32(data.msgValue),
+		tx_gas_price:       hashToBytes32(data.txGasPrice),
+		tx_origin:          addressToBytes20(data.txOrigin),
+		reentrant:          u32(data.reentrant),
+		tx_type:            u8(data.txType),
+		return_data_len:    0,
 	}
 }
 
@@ -75,6 +75,21 @@ u64 addPagesImpl(usize api, u16 pages);
 u64 addPagesWrap(usize api, u16 pages) {
     return addPagesImpl(api, pages);
 }
+
+u64 accountNonceImpl(usize api, Bytes20 address, u64 * cost);
+u64 accountNonceWrap(usize api, Bytes20 address, u64 * cost) {
+    return accountNonceImpl(api, address, cost);
+}
+
+Bytes32 accountStorageRootImpl(usize api, Bytes20 address, u64 * cost);
+Bytes32 accountStorageRootWrap(usize api, Bytes20 address, u64 * cost) {
+    return accountStorageRootImpl(api, address, cost);
+}
+
+EvmApiStatus selfDestructImpl(usize api, Bytes20 beneficiary, u64 * cost, RustVec * error);
+EvmApiStatus selfDestructWrap(usize api, Bytes20 beneficiary, u64 * cost, RustVec * error) {
+    return selfDestructImpl(api, beneficiary, cost, error);
+}
 */
 import "C"
 import (
@@ -100,19 +115,22 @@ func newApi(
 	apiClosures.Store(apiId, closures)
 	id := usize(apiId)
 	return C.GoEvmApi{
-		get_bytes32:      (*[0]byte)(C.getBytes32Wrap),
-		set_bytes32:      (*[0]byte)(C.setBytes32Wrap),
-		contract_call:    (*[0]byte)(C.contractCallWrap),
-		delegate_call:    (*[0]byte)(C.delegateCallWrap),
-		static_call:      (*[0]byte)(C.staticCallWrap),
-		create1:          (*[0]byte)(C.create1Wrap),
-		create2:          (*[0]byte)(C.create2Wrap),
-		get_return_data:  (*[0]byte)(C.getReturnDataWrap),
-		emit_log:         (*[0]byte)(C.emitLogWrap),
-		account_balance:  (*[0]byte)(C.accountBalanceWrap),
-		account_codehash: (*[0]byte)(C.accountCodeHashWrap),
-		add_pages:        (*[0]byte)(C.addPagesWrap),
-		id:               id,
+		get_bytes32:          (*[0]byte)(C.getBytes32Wrap),
+		set_bytes32:          (*[0]byte)(C.setBytes32Wrap),
+		contract_call:        (*[0]byte)(C.contractCallWrap),
+		delegate_call:        (*[0]byte)(C.delegateCallWrap),
+		static_call:          (*[0]byte)(C.staticCallWrap),
+		create1:              (*[0]byte)(C.create1Wrap),
+		create2:              (*[0]byte)(C.create2Wrap),
+		get_return_data:      (*[0]byte)(C.getReturnDataWrap),
+		emit_log:             (*[0]byte)(C.emitLogWrap),
+		account_balance:      (*[0]byte)(C.accountBalanceWrap),
+		account_codehash:     (*[0]byte)(C.accountCodeHashWrap),
+		add_pages:            (*[0]byte)(C.addPagesWrap),
+		selfdestruct:         (*[0]byte)(C.selfDestructWrap),
+		account_nonce:        (*[0]byte)(C.accountNonceWrap),
+		account_storage_root: (*[0]byte)(C.accountStorageRootWrap),
+		id:                   id,
 	}, id
 }
 
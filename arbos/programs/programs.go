@@ -222,6 +222,7 @@ func (p Programs) CallProgram(
 	tracingInfo *util.TracingInfo,
 	calldata []byte,
 	reentrant bool,
+	txType uint8,
 ) ([]byte, error) {
 
 	// ensure the program is runnable
@@ -272,19 +273,28 @@ func (p Programs) CallProgram(
 	statedb.AddStylusPages(program.footprint)
 	defer statedb.SetStylusPagesOpen(open)
 
+	// post-merge chains repurpose the difficulty slot for prevrandao
+	prevrandao := common.BigToHash(evm.Context.Difficulty)
+	if evm.Context.Random != nil {
+		prevrandao = *evm.Context.Random
+	}
+
 	evmData := &evmData{
-		blockBasefee:    common.BigToHash(evm.Context.BaseFee),
-		chainId:         evm.ChainConfig().ChainID.Uint64(),
-		blockCoinbase:   evm.Context.Coinbase,
-		blockGasLimit:   evm.Context.GasLimit,
-		blockNumber:     l1BlockNumber,
-		blockTimestamp:  evm.Context.Time,
-		contractAddress: scope.Contract.Address(),
-		msgSender:       scope.Contract.Caller(),
-		msgValue:        common.BigToHash(scope.Contract.Value()),
-		txGasPrice:      common.BigToHash(evm.TxContext.GasPrice),
-		txOrigin:        evm.TxContext.Origin,
-		reentrant:       arbmath.BoolToUint32(reentrant),
+		blockBasefee:     common.BigToHash(evm.Context.BaseFee),
+		blockPrevrandao:  prevrandao,
+		blockBlobBasefee: common.Hash{}, // blobs aren't supported
+		chainId:          evm.ChainConfig().ChainID.Uint64(),
+		blockCoinbase:    evm.Context.Coinbase,
+		blockGasLimit:    evm.Context.GasLimit,
+		blockNumber:      l1BlockNumber,
+		blockTimestamp:   evm.Context.Time,
+		contractAddress:  scope.Contract.Address(),
+		msgSender:        scope.Contract.Caller(),
+		msgValue:         common.BigToHash(scope.Contract.Value()),
+		txGasPrice:       common.BigToHash(evm.TxContext.GasPrice),
+		txOrigin:         evm.TxContext.Origin,
+		reentrant:        arbmath.BoolToUint32(reentrant),
+		txType:           txType,
 	}
 
 	address := contract.Address()
@@ -362,18 +372,21 @@ func (p Programs) goParams(version uint16, debug bool) (*goParams, error) {
 }
 
 type evmData struct {
-	blockBasefee    common.Hash
-	chainId         uint64
-	blockCoinbase   common.Address
-	blockGasLimit   uint64
-	blockNumber     uint64
-	blockTimestamp  uint64
-	contractAddress common.Address
-	msgSender       common.Address
-	msgValue        common.Hash
-	txGasPrice      common.Hash
-	txOrigin        common.Address
-	reentrant       uint32
+	blockBasefee     common.Hash
+	blockPrevrandao  common.Hash
+	blockBlobBasefee common.Hash
+	chainId          uint64
+	blockCoinbase    common.Address
+	blockGasLimit    uint64
+	blockNumber      uint64
+	blockTimestamp   uint64
+	contractAddress  common.Address
+	msgSender        common.Address
+	msgValue         common.Hash
+	txGasPrice       common.Hash
+	txOrigin         common.Address
+	reentrant        uint32
+	txType           uint8
 }
 
 type userStatus uint8
@@ -384,6 +397,8 @@ const (
 	userFailure
 	userOutOfInk
 	userOutOfStack
+	userInterrupted
+	userOutOfMemory
 )
 
 func (status userStatus) toResult(data []byte, debug bool) ([]byte, string, error) {
@@ -399,6 +414,10 @@ func (status userStatus) toResult(data []byte, debug bool) ([]byte, string, erro
 		return nil, "", vm.ErrOutOfGas
 	case userOutOfStack:
 		return nil, "", vm.ErrDepth
+	case userInterrupted:
+		return nil, "interrupted", vm.ErrExecutionReverted
+	case userOutOfMemory:
+		return nil, "out of memory", vm.ErrExecutionReverted
 	default:
 		log.Error("program errored with unknown status", "status", status, "data", msg)
 		return nil, msg, vm.ErrExecutionReverted
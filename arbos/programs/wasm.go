@@ -49,6 +49,8 @@ func rustMachineDropImpl(mach *rustMachine)
 func rustConfigImpl(version u16, maxDepth, inkPrice, debugMode u32) *rustConfig
 func rustEvmDataImpl(
 	blockBasefee *hash,
+	blockPrevrandao *hash,
+	blockBlobBasefee *hash,
 	chainId u64,
 	blockCoinbase *addr,
 	blockGasLimit u64,
@@ -60,6 +62,7 @@ func rustEvmDataImpl(
 	txGasPrice *hash,
 	txOrigin *addr,
 	reentrant u32,
+	txType u8,
 ) *rustEvmData
 
 func compileUserWasm(
@@ -143,6 +146,8 @@ func (p *goParams) encode() *rustConfig {
 func (d *evmData) encode() *rustEvmData {
 	return rustEvmDataImpl(
 		&d.blockBasefee,
+		&d.blockPrevrandao,
+		&d.blockBlobBasefee,
 		u64(d.chainId),
 		&d.blockCoinbase,
 		u64(d.blockGasLimit),
@@ -154,5 +159,6 @@ func (d *evmData) encode() *rustEvmData {
 		&d.txGasPrice,
 		&d.txOrigin,
 		u32(d.reentrant),
+		u8(d.txType),
 	)
 }
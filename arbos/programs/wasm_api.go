@@ -29,6 +29,9 @@ type apiWrapper struct {
 	addressBalance  js.Func
 	addressCodeHash js.Func
 	addPages        js.Func
+	selfDestruct    js.Func
+	accountNonce    js.Func
+	storageRoot     js.Func
 	funcs           []byte
 }
 
@@ -205,12 +208,27 @@ func newApi(
 		cost := closures.addPages(pages)
 		return write(stylus, cost)
 	})
+	selfDestruct := js.FuncOf(func(stylus js.Value, args []js.Value) any {
+		beneficiary := jsAddress(args[0])
+		cost, err := closures.selfDestruct(beneficiary)
+		return write(stylus, maybe(cost, err))
+	})
+	accountNonce := js.FuncOf(func(stylus js.Value, args []js.Value) any {
+		address := jsAddress(args[0])
+		nonce, cost := closures.accountNonce(address)
+		return write(stylus, nonce, cost)
+	})
+	storageRoot := js.FuncOf(func(stylus js.Value, args []js.Value) any {
+		address := jsAddress(args[0])
+		root, cost := closures.storageRoot(address)
+		return write(stylus, root, cost)
+	})
 
-	ids := make([]byte, 0, 12*4)
+	ids := make([]byte, 0, 15*4)
 	funcs := js.Global().Get("stylus").Call("setCallbacks",
 		getBytes32, setBytes32, contractCall, delegateCall,
 		staticCall, create1, create2, getReturnData, emitLog,
-		addressBalance, addressCodeHash, addPages,
+		addressBalance, addressCodeHash, addPages, selfDestruct, accountNonce, storageRoot,
 	)
 	for i := 0; i < funcs.Length(); i++ {
 		ids = append(ids, arbmath.Uint32ToBytes(u32(funcs.Index(i).Int()))...)
@@ -228,6 +246,9 @@ func newApi(
 		addressBalance:  addressBalance,
 		addressCodeHash: addressCodeHash,
 		addPages:        addPages,
+		selfDestruct:    selfDestruct,
+		accountNonce:    accountNonce,
+		storageRoot:     storageRoot,
 		funcs:           ids,
 	}
 }
@@ -245,4 +266,7 @@ func (api *apiWrapper) drop() {
 	api.addressBalance.Release()
 	api.addressCodeHash.Release()
 	api.addPages.Release()
+	api.selfDestruct.Release()
+	api.accountNonce.Release()
+	api.storageRoot.Release()
 }
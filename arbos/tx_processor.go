@@ -120,6 +120,12 @@ func (p *TxProcessor) ExecuteWASM(scope *vm.ScopeContext, input []byte, interpre
 	// reentrant if more than one open same-actor context span exists
 	reentrant := p.Programs[acting] > 1
 
+	// calls without an underlying tx, like eth_call, report the legacy type
+	txType := uint8(types.LegacyTxType)
+	if p.msg.Tx != nil {
+		txType = p.msg.Tx.Type()
+	}
+
 	return p.state.Programs().CallProgram(
 		scope,
 		p.evm.StateDB,
@@ -127,6 +133,7 @@ func (p *TxProcessor) ExecuteWASM(scope *vm.ScopeContext, input []byte, interpre
 		tracingInfo,
 		input,
 		reentrant,
+		txType,
 	)
 }
 